@@ -0,0 +1,642 @@
+use std::{cmp::Reverse, fmt::Display};
+
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use rust_decimal::Decimal;
+
+use crate::{
+    errors::KakeboError,
+    expenses::{group_expense::GroupExpense, repayment::Repayment, RecordId},
+    prompter::{choose, Prompter},
+    Expenses,
+};
+
+/// how a payment is spread over the open items between the user and a person
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// pay off the oldest open items first
+    OldestFirst,
+    /// pay off the largest open items first
+    LargestFirst,
+    /// look for open items that add up to exactly the received amount
+    ExactMatch,
+    /// pick the open items one by one
+    #[default]
+    Manual,
+}
+
+/// an open item a payment can be used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// a group part the person has not fully paid yet
+    GroupPart {
+        id: RecordId,
+        index: usize,
+    },
+    Advancement {
+        id: RecordId,
+    },
+    /// a group part the person paid more than their share of
+    OverpaidPart {
+        id: RecordId,
+        index: usize,
+    },
+    Debt {
+        id: RecordId,
+    },
+    /// the user's share of a group expense the person paid for
+    PayerShare {
+        id: RecordId,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Claim {
+    pub target: Target,
+    pub date: NaiveDate,
+    pub description: String,
+    pub open: Decimal,
+}
+
+impl Display for Claim {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:8.2} open: {}", self.open, self.description)
+    }
+}
+
+#[derive(Debug)]
+pub struct Allocation {
+    pub claim: Claim,
+    pub amount: Decimal,
+}
+
+/// which way money went between the user and a person
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Received,
+    Paid,
+}
+
+/// a payment spread over the open items, with what is left of it as the person's overflow
+#[derive(Debug)]
+pub struct Settlement {
+    pub person: String,
+    pub allocations: Vec<Allocation>,
+    pub old_overflow: Decimal,
+    /// money the person paid that is not assigned yet, a negative overflow is a credit
+    pub overflow: Decimal,
+}
+
+impl Settlement {
+    pub fn changes_anything(&self) -> bool {
+        !self.allocations.is_empty() || self.overflow != self.old_overflow
+    }
+}
+
+/// the maximum number of open items searched for an exact match, the oldest ones are searched
+pub const EXACT_MATCH_LIMIT: usize = 20;
+
+/// fills the claims in the given order, the last one may only be paid in part
+fn allocate_greedy(claims: Vec<Claim>, available: Decimal) -> Vec<Allocation> {
+    let mut remaining = available;
+    let mut allocations = Vec::new();
+    for claim in claims {
+        let amount = remaining.min(claim.open);
+        if amount <= Decimal::ZERO {
+            break;
+        }
+        remaining -= amount;
+        allocations.push(Allocation { claim, amount });
+    }
+    allocations
+}
+
+/// the indices of claims whose open amounts add up to exactly `target`
+fn exact_subset(claims: &[Claim], target: Decimal) -> Option<Vec<usize>> {
+    fn search(claims: &[Claim], start: usize, target: Decimal, chosen: &mut Vec<usize>) -> bool {
+        if target.is_zero() {
+            return true;
+        }
+        for i in start..claims.len() {
+            if claims[i].open <= target {
+                chosen.push(i);
+                if search(claims, i + 1, target - claims[i].open, chosen) {
+                    return true;
+                }
+                chosen.pop();
+            }
+        }
+        false
+    }
+    let considered = &claims[..claims.len().min(EXACT_MATCH_LIMIT)];
+    let mut chosen = Vec::new();
+    search(considered, 0, target, &mut chosen).then_some(chosen)
+}
+
+/// lets the user pick the open items one by one until the amount is used up
+pub fn allocate_manually(
+    prompter: &mut dyn Prompter,
+    mut claims: Vec<Claim>,
+    available: Decimal,
+) -> Result<Vec<Allocation>, KakeboError> {
+    claims.sort_by_key(|claim| claim.date);
+    let mut remaining = available;
+    let mut allocations = Vec::new();
+    while remaining > Decimal::ZERO {
+        prompter.message(&format!("There is {} unassigned", remaining));
+        if claims.is_empty() {
+            break;
+        }
+        let selected = match choose(prompter, "Which open item did this pay?", claims.clone()) {
+            Err(KakeboError::PromptCanceled) => break,
+            selected => selected?,
+        };
+        claims.retain(|claim| claim.target != selected.target);
+        let amount = remaining.min(selected.open);
+        remaining -= amount;
+        allocations.push(Allocation {
+            claim: selected,
+            amount,
+        });
+    }
+    Ok(allocations)
+}
+
+/// pays off the oldest open items first
+pub fn oldest_first(mut claims: Vec<Claim>, available: Decimal) -> Vec<Allocation> {
+    claims.sort_by_key(|claim| claim.date);
+    allocate_greedy(claims, available)
+}
+
+/// pays off the largest open items first, older ones first among equal amounts
+pub fn largest_first(mut claims: Vec<Claim>, available: Decimal) -> Vec<Allocation> {
+    claims.sort_by_key(|claim| (Reverse(claim.open), claim.date));
+    allocate_greedy(claims, available)
+}
+
+/// pays off open items that add up to exactly the available amount, if there are any
+///
+/// Only the oldest [`EXACT_MATCH_LIMIT`] open items are searched.
+pub fn exact_match(claims: &[Claim], available: Decimal) -> Option<Vec<Allocation>> {
+    let mut claims = claims.to_vec();
+    claims.sort_by_key(|claim| claim.date);
+    let indices = exact_subset(&claims, available)?;
+    Some(
+        indices
+            .into_iter()
+            .map(|i| Allocation {
+                amount: claims[i].open,
+                claim: claims[i].clone(),
+            })
+            .collect(),
+    )
+}
+
+/// spreads the available money over the claims as the strategy says
+///
+/// If no exact match is found, the user is told and the oldest items are paid off first.
+pub fn allocate(
+    prompter: &mut dyn Prompter,
+    claims: Vec<Claim>,
+    available: Decimal,
+    strategy: Strategy,
+) -> Result<Vec<Allocation>, KakeboError> {
+    let allocations = match strategy {
+        Strategy::OldestFirst => oldest_first(claims, available),
+        Strategy::LargestFirst => largest_first(claims, available),
+        Strategy::ExactMatch => match exact_match(&claims, available) {
+            Some(allocations) => allocations,
+            None => {
+                let searched = if claims.len() > EXACT_MATCH_LIMIT {
+                    format!(
+                        "the oldest {} of {} open items",
+                        EXACT_MATCH_LIMIT,
+                        claims.len()
+                    )
+                } else {
+                    "the open items".to_string()
+                };
+                prompter.message(&format!(
+                    "No combination of {} adds up to exactly {}, paying the oldest first.",
+                    searched, available
+                ));
+                oldest_first(claims, available)
+            }
+        },
+        Strategy::Manual => allocate_manually(prompter, claims, available)?,
+    };
+    Ok(allocations)
+}
+
+impl Expenses {
+    /// everything the person still owes the user that a payment can be allocated to
+    pub fn open_claims(&self, person: &str) -> Vec<Claim> {
+        let parts = self
+            .group_expenses
+            .iter()
+            .flat_map(|group_expense| group_expense.parts())
+            .filter(|part| part.person == person)
+            .filter(|part| part.to_pay > part.paid.unwrap_or(Decimal::ZERO))
+            .map(|part| Claim {
+                target: Target::GroupPart {
+                    id: part.info.id,
+                    index: part.index,
+                },
+                date: part.info.date,
+                open: part.to_pay - part.paid.unwrap_or(Decimal::ZERO),
+                description: part.to_string(),
+            });
+        let advancements = self
+            .unpaid_advancements
+            .iter()
+            .filter(|advancement| advancement.person == person)
+            .filter(|advancement| advancement.outstanding() > Decimal::ZERO)
+            .map(|advancement| Claim {
+                target: Target::Advancement { id: advancement.id },
+                date: advancement.date,
+                open: advancement.outstanding(),
+                description: advancement.to_string(),
+            });
+        parts.chain(advancements).collect()
+    }
+
+    /// everything the user still owes the person that a payment can be allocated to
+    pub fn owed_claims(&self, person: &str) -> Vec<Claim> {
+        let parts = self
+            .group_expenses
+            .iter()
+            .flat_map(|group_expense| group_expense.parts())
+            .filter(|part| part.person == person)
+            .filter(|part| part.paid.unwrap_or(Decimal::ZERO) > part.to_pay)
+            .map(|part| Claim {
+                target: Target::OverpaidPart {
+                    id: part.info.id,
+                    index: part.index,
+                },
+                date: part.info.date,
+                open: part.paid.unwrap_or(Decimal::ZERO) - part.to_pay,
+                description: part.to_string(),
+            });
+        let debts = self
+            .debts_owed
+            .iter()
+            .filter(|debt| debt.person == person)
+            .filter(|debt| debt.outstanding() > Decimal::ZERO)
+            .map(|debt| Claim {
+                target: Target::Debt {
+                    id: debt.expense.info.id,
+                },
+                date: debt.expense.info.date,
+                open: debt.outstanding(),
+                description: debt.to_string(),
+            });
+        let shares = self
+            .group_expenses
+            .iter()
+            .filter(|group_expense| group_expense.payer.as_deref() == Some(person))
+            .filter(|group_expense| group_expense.user_outstanding() > Decimal::ZERO)
+            .map(|group_expense| Claim {
+                target: Target::PayerShare {
+                    id: group_expense.info.id,
+                },
+                date: group_expense.info.date,
+                open: group_expense.user_outstanding(),
+                description: group_expense.to_string(),
+            });
+        parts.chain(debts).chain(shares).collect()
+    }
+
+    /// spreads money received from or paid to the person over what is open between them,
+    /// together with what is left over from earlier payments
+    pub fn settle(
+        &self,
+        prompter: &mut dyn Prompter,
+        direction: Direction,
+        person: &str,
+        value: Decimal,
+        strategy: Strategy,
+    ) -> Result<Settlement, KakeboError> {
+        let old_overflow = *self.overflows.get(person).unwrap_or(&Decimal::ZERO);
+        let (available, claims) = match direction {
+            Direction::Received => {
+                let available = value + old_overflow;
+                prompter.message(&format!(
+                    "There is {} + {} = {} unassigned",
+                    value, old_overflow, available
+                ));
+                (available, self.open_claims(person))
+            }
+            Direction::Paid => {
+                let available = value - old_overflow;
+                prompter.message(&format!(
+                    "There is {} - {} = {} unassigned",
+                    value, old_overflow, available
+                ));
+                (available, self.owed_claims(person))
+            }
+        };
+        let allocations = allocate(prompter, claims, available, strategy)?;
+        let allocated: Decimal = allocations.iter().map(|alloc| alloc.amount).sum();
+        let overflow = match direction {
+            Direction::Received => available - allocated,
+            Direction::Paid => allocated - available,
+        };
+        Ok(Settlement {
+            person: person.to_string(),
+            allocations,
+            old_overflow,
+            overflow,
+        })
+    }
+
+    pub fn apply_settlement(&mut self, settlement: Settlement) {
+        self.apply_allocations(&settlement.allocations);
+        self.overflows
+            .insert(settlement.person, settlement.overflow);
+    }
+
+    pub fn apply_allocations(&mut self, allocations: &[Allocation]) {
+        for allocation in allocations {
+            match allocation.claim.target {
+                Target::GroupPart { id, index } => {
+                    self.add_paid_amount(id, index, allocation.amount);
+                }
+                Target::OverpaidPart { id, index } => {
+                    self.add_paid_amount(id, index, -allocation.amount);
+                }
+                Target::Advancement { id } => {
+                    let idx = self
+                        .unpaid_advancements
+                        .iter()
+                        .position(|advancement| advancement.id == id)
+                        .expect("Claims are only created for existing advancements");
+                    self.repay_advancement(idx, Repayment::today(allocation.amount));
+                }
+                Target::PayerShare { id } => {
+                    self.group_expense_mut(id)
+                        .repayments
+                        .push(Repayment::today(allocation.amount));
+                }
+                Target::Debt { id } => {
+                    let idx = self
+                        .debts_owed
+                        .iter()
+                        .position(|debt| debt.expense.info.id == id)
+                        .expect("Claims are only created for existing debts");
+                    self.repay_debt(idx, Repayment::today(allocation.amount));
+                }
+            }
+        }
+    }
+
+    fn group_expense_mut(&mut self, id: RecordId) -> &mut GroupExpense {
+        self.group_expenses
+            .iter_mut()
+            .find(|group_expense| group_expense.info.id == id)
+            .expect("Claims are only created for existing group expenses")
+    }
+
+    fn add_paid_amount(&mut self, id: RecordId, index: usize, amount: Decimal) {
+        let group_expense = self.group_expense_mut(id);
+        let paid = group_expense.paid_amounts[index].unwrap_or(Decimal::ZERO);
+        group_expense.paid_amounts[index] = Some(paid + amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prompter::ScriptedPrompter;
+    use crate::testing::{add_advancement, add_debt, add_group, amount, date};
+
+    use super::*;
+
+    /// Anna owes 12 from a dinner on 2026-03-01 and 40 from an advancement on 2026-03-02
+    fn expenses() -> Expenses {
+        let mut expenses = Expenses::default();
+        add_group(
+            &mut expenses,
+            "2026-03-01",
+            "Dinner",
+            "10",
+            &[("Anna", "12")],
+            None,
+        );
+        add_advancement(&mut expenses, "2026-03-02", "Anna", "40");
+        expenses
+    }
+
+    fn amounts(allocations: &[Allocation]) -> Vec<Decimal> {
+        allocations
+            .iter()
+            .map(|allocation| allocation.amount)
+            .collect()
+    }
+
+    #[test]
+    fn oldest_first_pays_the_last_item_in_part() {
+        let claims = expenses().open_claims("Anna");
+        let allocations = oldest_first(claims.clone(), amount("20"));
+        assert_eq!(amounts(&allocations), [amount("12"), amount("8")]);
+        assert!(matches!(
+            allocations[1].claim.target,
+            Target::Advancement { .. }
+        ));
+
+        // whatever is left over after everything is paid is not allocated
+        let allocations = oldest_first(claims, amount("100"));
+        assert_eq!(amounts(&allocations), [amount("12"), amount("40")]);
+    }
+
+    #[test]
+    fn largest_first_starts_with_the_advancement() {
+        let claims = expenses().open_claims("Anna");
+        let allocations = largest_first(claims.clone(), amount("20"));
+        assert_eq!(amounts(&allocations), [amount("20")]);
+        let allocations = largest_first(claims, amount("45"));
+        assert_eq!(amounts(&allocations), [amount("40"), amount("5")]);
+    }
+
+    #[test]
+    fn exact_match_finds_items_that_add_up() {
+        let claims = expenses().open_claims("Anna");
+        let allocations = exact_match(&claims, amount("40")).unwrap();
+        assert_eq!(amounts(&allocations), [amount("40")]);
+        let allocations = exact_match(&claims, amount("52")).unwrap();
+        assert_eq!(amounts(&allocations), [amount("12"), amount("40")]);
+        assert!(exact_match(&claims, amount("30")).is_none());
+    }
+
+    #[test]
+    fn a_missing_exact_match_is_reported() {
+        let claims = expenses().open_claims("Anna");
+        let mut prompter = ScriptedPrompter::new::<_, &str>([]);
+        let allocations =
+            allocate(&mut prompter, claims, amount("30"), Strategy::ExactMatch).unwrap();
+        assert_eq!(amounts(&allocations), [amount("12"), amount("18")]);
+        assert_eq!(
+            prompter.messages(),
+            ["No combination of the open items adds up to exactly 30, paying the oldest first."]
+        );
+    }
+
+    #[test]
+    fn only_the_oldest_items_are_searched_for_an_exact_match() {
+        let claims: Vec<_> = (1..=25)
+            .map(|day| Claim {
+                target: Target::Advancement {
+                    id: day.to_string().parse().unwrap(),
+                },
+                date: date(2026, 3, day as u32),
+                description: format!("Advancement {}", day),
+                open: if day == 25 { amount("5") } else { amount("1") },
+            })
+            .collect();
+        assert!(exact_match(&claims, amount("5")).is_some());
+        assert!(exact_match(&claims, amount("25")).is_none());
+
+        let mut prompter = ScriptedPrompter::new::<_, &str>([]);
+        let allocations =
+            allocate(&mut prompter, claims, amount("25"), Strategy::ExactMatch).unwrap();
+        assert_eq!(allocations.len(), 25);
+        assert_eq!(
+            prompter.messages(),
+            [
+                "No combination of the oldest 20 of 25 open items adds up to exactly 25, \
+              paying the oldest first."
+            ]
+        );
+    }
+
+    #[test]
+    fn manual_allocation_follows_the_choices() {
+        let claims = expenses().open_claims("Anna");
+        let mut prompter = ScriptedPrompter::new(["2"]);
+        let allocations = allocate_manually(&mut prompter, claims.clone(), amount("20")).unwrap();
+        assert_eq!(amounts(&allocations), [amount("20")]);
+
+        // the money left over after every item is picked stays unassigned
+        let mut prompter = ScriptedPrompter::new(["1", "1"]);
+        let allocations = allocate_manually(&mut prompter, claims.clone(), amount("60")).unwrap();
+        assert_eq!(amounts(&allocations), [amount("12"), amount("40")]);
+        assert_eq!(prompter.messages().last().unwrap(), "There is 8 unassigned");
+
+        let mut prompter = ScriptedPrompter::new(["<esc>"]);
+        assert!(allocate_manually(&mut prompter, claims, amount("60"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn applied_allocations_settle_the_items() {
+        let mut expenses = expenses();
+        let allocations = oldest_first(expenses.open_claims("Anna"), amount("20"));
+        expenses.apply_allocations(&allocations);
+        assert_eq!(
+            expenses.group_expenses[0].paid_amounts[0],
+            Some(amount("12"))
+        );
+        assert_eq!(expenses.unpaid_advancements[0].outstanding(), amount("32"));
+        assert_eq!(expenses.ledger("Anna").balance(), amount("32"));
+
+        // paying more than a group part turns it into something the user owes
+        expenses.apply_allocations(&[Allocation {
+            claim: expenses.open_claims("Anna")[0].clone(),
+            amount: amount("35"),
+        }]);
+        assert!(expenses.unpaid_advancements.is_empty());
+        let allocations = oldest_first(expenses.open_claims("Anna"), amount("5"));
+        assert!(allocations.is_empty());
+    }
+
+    #[test]
+    fn owed_items_are_paid_back() {
+        let mut expenses = expenses();
+        expenses.group_expenses[0].paid_amounts[0] = Some(amount("15"));
+        add_debt(&mut expenses, "2026-03-03", "Anna", "20");
+        add_group(
+            &mut expenses,
+            "2026-03-04",
+            "Cinema",
+            "9",
+            &[("Anna", "9")],
+            Some("Anna"),
+        );
+        let claims = expenses.owed_claims("Anna");
+        assert_eq!(
+            claims.iter().map(|claim| claim.open).collect::<Vec<_>>(),
+            [amount("3"), amount("20"), amount("9")]
+        );
+
+        expenses.apply_allocations(&oldest_first(claims, amount("32")));
+        assert!(expenses.owed_claims("Anna").is_empty());
+        assert_eq!(
+            expenses.group_expenses[0].paid_amounts[0],
+            Some(amount("12"))
+        );
+        assert!(expenses.debts_owed.is_empty());
+        assert_eq!(expenses.group_expenses[1].user_outstanding(), Decimal::ZERO);
+    }
+
+    fn settle(
+        expenses: &mut Expenses,
+        direction: Direction,
+        value: &str,
+    ) -> (Vec<Decimal>, Decimal, Vec<String>) {
+        let mut prompter = ScriptedPrompter::new::<_, &str>([]);
+        let settlement = expenses
+            .settle(
+                &mut prompter,
+                direction,
+                "Anna",
+                amount(value),
+                Strategy::OldestFirst,
+            )
+            .unwrap();
+        let result = (amounts(&settlement.allocations), settlement.overflow);
+        expenses.apply_settlement(settlement);
+        (result.0, result.1, prompter.messages().to_vec())
+    }
+
+    #[test]
+    fn paying_too_much_leaves_a_credit_for_the_next_payment() {
+        let mut expenses = Expenses::default();
+        add_debt(&mut expenses, "2026-03-01", "Anna", "20");
+
+        let (allocated, overflow, messages) = settle(&mut expenses, Direction::Paid, "30");
+        assert_eq!(allocated, [amount("20")]);
+        assert_eq!(overflow, amount("-10"));
+        assert_eq!(messages, ["There is 30 - 0 = 30 unassigned"]);
+        assert!(expenses.debts_owed.is_empty());
+        assert_eq!(expenses.ledger("Anna").balance(), amount("10"));
+
+        add_debt(&mut expenses, "2026-03-02", "Anna", "15");
+        let (allocated, overflow, messages) = settle(&mut expenses, Direction::Paid, "10");
+        assert_eq!(allocated, [amount("15")]);
+        assert_eq!(overflow, amount("-5"));
+        assert_eq!(messages, ["There is 10 - -10 = 20 unassigned"]);
+    }
+
+    #[test]
+    fn a_credit_is_used_up_by_money_received() {
+        let mut expenses = Expenses::default();
+        add_advancement(&mut expenses, "2026-03-01", "Anna", "40");
+        expenses.overflows.insert("Anna".to_string(), amount("-5"));
+
+        let (allocated, overflow, _) = settle(&mut expenses, Direction::Received, "30");
+        assert_eq!(allocated, [amount("25")]);
+        assert_eq!(overflow, Decimal::ZERO);
+        assert_eq!(expenses.unpaid_advancements[0].outstanding(), amount("15"));
+
+        // receiving more than is owed leaves an overflow the user owes back
+        let (allocated, overflow, messages) = settle(&mut expenses, Direction::Received, "20");
+        assert_eq!(allocated, [amount("15")]);
+        assert_eq!(overflow, amount("5"));
+        assert_eq!(messages, ["There is 20 + 0 = 20 unassigned"]);
+
+        // paying less than the overflow leaves the rest still owed
+        let (allocated, overflow, _) = settle(&mut expenses, Direction::Paid, "3");
+        assert!(allocated.is_empty());
+        assert_eq!(overflow, amount("2"));
+        assert_eq!(expenses.ledger("Anna").balance(), amount("-2"));
+    }
+}
@@ -0,0 +1,359 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+};
+
+use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::{expenses::RecordId, DisplayableExpense, Expenses};
+
+/// what an audit entry is about
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AuditSubject {
+    Record(RecordId),
+    Overflow(String),
+}
+
+impl Display for AuditSubject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditSubject::Record(id) => write!(f, "record [{}]", id),
+            AuditSubject::Overflow(person) => write!(f, "overflow of {}", person),
+        }
+    }
+}
+
+/// the fields of a record as they are shown in the history, in the order they are shown
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditFields(Vec<(String, String)>);
+
+impl AuditFields {
+    /// `field` has to be unique within the record, e.g. `occurrence 2026-03-01`
+    pub fn add(&mut self, field: impl Display, value: impl Display) {
+        self.0.push((field.to_string(), value.to_string()));
+    }
+
+    fn values(&self) -> HashMap<&str, &str> {
+        self.0
+            .iter()
+            .map(|(field, value)| (field.as_str(), value.as_str()))
+            .collect()
+    }
+}
+
+/// a record whose changes are kept in the history
+pub trait Audited {
+    fn audit_fields(&self, fields: &mut AuditFields);
+}
+
+/// the value of a field before and after a command, `None` if the field did not exist
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl FieldChange {
+    /// the fields that differ between two states of a record, in the order they are shown
+    fn between(before: &AuditFields, after: &AuditFields) -> Vec<Self> {
+        let (old_values, new_values) = (before.values(), after.values());
+        let changed = before
+            .0
+            .iter()
+            .filter(|(field, value)| new_values.get(field.as_str()) != Some(&value.as_str()))
+            .map(|(field, value)| Self {
+                field: field.clone(),
+                before: Some(value.clone()),
+                after: new_values
+                    .get(field.as_str())
+                    .map(|value| value.to_string()),
+            });
+        let added = after
+            .0
+            .iter()
+            .filter(|(field, _)| !old_values.contains_key(field.as_str()))
+            .map(|(field, value)| Self {
+                field: field.clone(),
+                before: None,
+                after: Some(value.clone()),
+            });
+        changed.chain(added).collect()
+    }
+}
+
+impl Display for FieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = Vec::new();
+        if let Some(before) = &self.before {
+            lines.push(format!("- {}: {}", self.field, before));
+        }
+        if let Some(after) = &self.after {
+            lines.push(format!("+ {}: {}", self.field, after));
+        }
+        write!(f, "{}", lines.join("\n  "))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AuditAction {
+    Added,
+    Changed,
+    Removed,
+}
+
+impl Display for AuditAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditAction::Added => write!(f, "added"),
+            AuditAction::Changed => write!(f, "changed"),
+            AuditAction::Removed => write!(f, "removed"),
+        }
+    }
+}
+
+/// the fields of a single record that a command changed, with their old and new values
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub timestamp: NaiveDateTime,
+    pub command: String,
+    pub subject: AuditSubject,
+    pub action: AuditAction,
+    pub changes: Vec<FieldChange>,
+}
+
+impl Display for AuditEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} (kakebo {})",
+            self.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            self.subject,
+            self.action,
+            self.command
+        )?;
+        for change in &self.changes {
+            write!(f, "\n  {}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// the append-only trail of every change to the database
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuditLog(Vec<AuditEntry>);
+
+impl AuditLog {
+    /// appends an entry for every subject whose fields differ between `before` and `after`
+    pub fn record_changes(
+        &mut self,
+        command: &str,
+        before: &BTreeMap<AuditSubject, AuditFields>,
+        after: &BTreeMap<AuditSubject, AuditFields>,
+    ) {
+        let timestamp = Local::now().naive_local();
+        let nothing = AuditFields::default();
+        let subjects: Vec<_> = before
+            .keys()
+            .chain(after.keys().filter(|subject| !before.contains_key(subject)))
+            .collect();
+        for subject in subjects {
+            let (action, old, new) = match (before.get(subject), after.get(subject)) {
+                (Some(old), Some(new)) if old == new => continue,
+                (Some(old), Some(new)) => (AuditAction::Changed, old, new),
+                (Some(old), None) => (AuditAction::Removed, old, &nothing),
+                (None, Some(new)) => (AuditAction::Added, &nothing, new),
+                (None, None) => unreachable!("The subjects are taken from both states"),
+            };
+            self.0.push(AuditEntry {
+                timestamp,
+                command: command.to_string(),
+                subject: subject.clone(),
+                action,
+                changes: FieldChange::between(old, new),
+            });
+        }
+    }
+
+    /// the entries about the given record, or all of them
+    pub fn entries(&self, record: Option<RecordId>) -> impl Iterator<Item = &AuditEntry> + use<'_> {
+        self.0
+            .iter()
+            .filter(move |entry| record.is_none_or(|id| entry.subject == AuditSubject::Record(id)))
+    }
+}
+
+fn record_states<T: DisplayableExpense + Audited>(
+    records: &[T],
+) -> impl Iterator<Item = (AuditSubject, AuditFields)> + use<'_, T> {
+    records.iter().map(|record| {
+        let mut fields = AuditFields::default();
+        record.audit_fields(&mut fields);
+        (AuditSubject::Record(record.id()), fields)
+    })
+}
+
+impl Expenses {
+    /// the current fields of every record and overflow, used to find out what a command changed
+    pub fn audit_states(&self) -> BTreeMap<AuditSubject, AuditFields> {
+        let overflows = self.overflows.iter().map(|(person, overflow)| {
+            let mut fields = AuditFields::default();
+            fields.add("overflow", format!("{:.2}", overflow));
+            (AuditSubject::Overflow(person.clone()), fields)
+        });
+        record_states(&self.single_expenses)
+            .chain(record_states(&self.group_expenses))
+            .chain(record_states(&self.recurring_expenses))
+            .chain(record_states(&self.debts_owed))
+            .chain(record_states(&self.unpaid_advancements))
+            .chain(overflows)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use crate::expenses::recurring_expense::{Occurrence, OccurrenceStatus};
+    use crate::testing::{add_single, amount, date, recurring};
+
+    use super::*;
+
+    fn change(field: &str, before: Option<&str>, after: Option<&str>) -> FieldChange {
+        FieldChange {
+            field: field.to_string(),
+            before: before.map(str::to_string),
+            after: after.map(str::to_string),
+        }
+    }
+
+    /// records what `command` changed and returns the new entries
+    fn record(expenses: &mut Expenses, command: impl FnOnce(&mut Expenses)) -> Vec<&AuditEntry> {
+        let before = expenses.audit_states();
+        command(expenses);
+        let after = expenses.audit_states();
+        let known = expenses.history.0.len();
+        expenses.history.record_changes("test", &before, &after);
+        expenses.history.0[known..].iter().collect()
+    }
+
+    #[test]
+    fn only_the_changed_fields_are_kept() {
+        let mut expenses = Expenses::default();
+        let entries = record(&mut expenses, |expenses| {
+            add_single(expenses, "2026-03-01", "Food", "Lunch", "10");
+        });
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, AuditAction::Added);
+        assert_eq!(
+            entries[0].changes,
+            [
+                change("date", None, Some("2026-03-01")),
+                change("category", None, Some("Food")),
+                change("description", None, Some("Lunch")),
+                change("amount", None, Some("10.00")),
+            ]
+        );
+
+        let entries = record(&mut expenses, |expenses| {
+            expenses.single_expenses[0].amount = amount("12");
+            expenses.single_expenses[0].info.description = None;
+        });
+        assert_eq!(entries[0].action, AuditAction::Changed);
+        assert_eq!(
+            entries[0].changes,
+            [
+                change("description", Some("Lunch"), None),
+                change("amount", Some("10.00"), Some("12.00")),
+            ]
+        );
+        assert!(entries[0]
+            .to_string()
+            .ends_with("\n  - description: Lunch\n  - amount: 10.00\n  + amount: 12.00"));
+
+        let entries = record(&mut expenses, |expenses| {
+            expenses.single_expenses.clear();
+        });
+        assert_eq!(entries[0].action, AuditAction::Removed);
+        assert!(entries[0]
+            .changes
+            .iter()
+            .all(|change| change.after.is_none()));
+    }
+
+    #[test]
+    fn unchanged_records_are_left_out() {
+        let mut expenses = Expenses::default();
+        add_single(&mut expenses, "2026-03-01", "Food", "Lunch", "10");
+        let entries = record(&mut expenses, |expenses| {
+            add_single(expenses, "2026-03-02", "Food", "Dinner", "20");
+            expenses
+                .overflows
+                .insert("Anna".to_string(), Decimal::from(5));
+        });
+        let subjects: Vec<_> = entries.iter().map(|entry| entry.subject.clone()).collect();
+        assert_eq!(
+            subjects,
+            [
+                AuditSubject::Record(expenses.last_id),
+                AuditSubject::Overflow("Anna".to_string())
+            ]
+        );
+        assert!(record(&mut expenses, |_| {}).is_empty());
+    }
+
+    #[test]
+    fn entries_can_be_limited_to_one_record() {
+        let mut expenses = Expenses::default();
+        let first = add_single(&mut expenses, "2026-03-01", "Food", "Lunch", "10");
+        record(&mut expenses, |expenses| {
+            add_single(expenses, "2026-03-02", "Food", "Dinner", "20");
+        });
+        record(&mut expenses, |expenses| {
+            expenses.single_expenses[0].amount = amount("11");
+            expenses.single_expenses[1].amount = amount("21");
+        });
+
+        assert_eq!(expenses.history.entries(None).count(), 3);
+        let changes: Vec<_> = expenses
+            .history
+            .entries(Some(first))
+            .map(|entry| (entry.action, entry.changes.len()))
+            .collect();
+        assert_eq!(changes, [(AuditAction::Changed, 1)]);
+        assert_eq!(expenses.history.entries(Some(expenses.last_id)).count(), 2);
+    }
+
+    #[test]
+    fn a_new_occurrence_is_a_single_change() {
+        let mut expenses = Expenses::default();
+        let mut rent = recurring("2024-01-01", "Day(s)", "1");
+        let mut day = date(2024, 1, 1);
+        while day < date(2026, 9, 19) {
+            rent.occurrences.push(Occurrence {
+                date: day,
+                amount: rent.amount,
+                status: OccurrenceStatus::Paid,
+                group_id: None,
+            });
+            day = day.succ_opt().unwrap();
+        }
+        expenses.recurring_expenses.push(rent);
+
+        let entries = record(&mut expenses, |expenses| {
+            expenses.recurring_expenses[0].occurrences.push(Occurrence {
+                date: date(2026, 9, 19),
+                amount: amount("50"),
+                status: OccurrenceStatus::Due,
+                group_id: None,
+            });
+        });
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].changes,
+            [change("occurrence 2026-09-19", None, Some("50.00 due"))]
+        );
+    }
+}
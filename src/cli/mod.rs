@@ -0,0 +1,176 @@
+use kakebo::{
+    allocation::Allocation,
+    audit::AuditLog,
+    expenses::RecordId,
+    journal::Journal,
+    ledger::{Ledger, BALANCE_HEADER},
+    report::{Grouping, Report},
+    search::{self, SearchEntry},
+    upcoming::Forecast,
+    Expenses, KakeboConfig,
+};
+use rust_decimal::Decimal;
+
+pub fn print_status(expenses: &Expenses) {
+    println!("Expenses Overview:");
+    let status = expenses.status();
+    println!(
+        "  Single Expenses last month:    {:8.2}",
+        status.single_last_month
+    );
+    println!(
+        "  Group Expenses last month:     {:8.2}",
+        status.group_last_month
+    );
+    println!(
+        "  Recurring Expenses last month: {:8.2}",
+        status.recurring_last_month
+    );
+    println!(
+        "  Total Expenses last month:     {:8.2}",
+        status.total_last_month()
+    );
+
+    println!("Balances:");
+    println!("{}", BALANCE_HEADER);
+    for ledger in expenses.ledgers() {
+        println!("{}", ledger.balance_line());
+    }
+}
+
+pub fn print_ledger(ledger: &Ledger, include_settled: bool) {
+    for item in &ledger.items {
+        if include_settled || !item.is_settled() {
+            println!("  {}", item);
+        }
+    }
+    println!("{}", BALANCE_HEADER);
+    println!("{}", ledger.balance_line());
+}
+
+pub fn print_entries(entries: &[SearchEntry], by: Option<Grouping>, config: &KakeboConfig) {
+    if entries.is_empty() {
+        println!("No matching entries.");
+        return;
+    }
+    println!(
+        "{:>5} {:10} {:10} {:22} {:30} {:20} {:>8}",
+        "ID", "Date", "Type", "Category", "Description", "People", "Amount"
+    );
+    for entry in entries {
+        print!("{}{}", entry, config.currency);
+        for tag in &entry.tags {
+            print!(" #{}", tag);
+        }
+        println!();
+    }
+
+    println!("Totals:");
+    for (group, (count, total)) in search::totals(entries, by) {
+        println!(
+            "  {:22} {:4} entries {:8.2}{}",
+            group, count, total, config.currency
+        );
+    }
+    let total: Decimal = entries.iter().map(|entry| entry.amount).sum();
+    println!(
+        "  {:22} {:4} entries {:8.2}{}",
+        "all",
+        entries.len(),
+        total,
+        config.currency
+    );
+}
+
+pub fn print_report(report: &Report, by: Grouping, config: &KakeboConfig) {
+    println!("Expenses from {} to {}:", report.start, report.end);
+    if report.expense_count == 0 {
+        println!("  No expenses in this interval.");
+        return;
+    }
+    for row in &report.rows {
+        let name = format!("{}{}", "  ".repeat(row.depth), row.name);
+        println!("  {:30} {:8.2}{}", name, row.total, config.currency);
+    }
+    println!("  {:30} {:8.2}{}", "Total", report.total, config.currency);
+    if by == Grouping::Tag {
+        println!("  (expenses with several tags are counted once per tag)");
+    }
+}
+
+pub fn print_upcoming(forecast: &Forecast, config: &KakeboConfig) {
+    println!(
+        "Upcoming bills from {} to {}:",
+        forecast.start, forecast.end
+    );
+    if forecast.bills.is_empty() {
+        println!("  No recurring expenses are due in this interval.");
+    }
+    for bill in &forecast.bills {
+        println!(
+            "  {} {:50} {:8.2}{}",
+            bill.date,
+            bill.expense.info.title(),
+            bill.amount,
+            config.currency
+        );
+    }
+    println!(
+        "  {:61} {:8.2}{}",
+        "Total",
+        forecast.total(),
+        config.currency
+    );
+
+    if let Some((month, projected)) = forecast.next_month {
+        println!(
+            "Projected recurring expenses for {}: {:.2}{}",
+            month.format("%B %Y"),
+            projected,
+            config.currency
+        );
+    }
+    if !forecast.ending.is_empty() {
+        println!("Ending soon:");
+        for expense in &forecast.ending {
+            if let Some(end_date) = expense.end_date() {
+                println!("  {} ends on {}", expense.info, end_date);
+            }
+        }
+    }
+}
+
+pub fn print_preview(allocations: &[Allocation], config: &KakeboConfig) {
+    println!("Allocation:");
+    for allocation in allocations {
+        println!(
+            "  {:8.2}{} -> {}",
+            allocation.amount, config.currency, allocation.claim.description
+        );
+    }
+}
+
+pub fn print_log(journal: &Journal, count: usize) {
+    if journal.is_empty() {
+        println!("No operations recorded.");
+        return;
+    }
+    for (number, operation, undone) in journal.recent(count) {
+        let marker = if undone { "u" } else { " " };
+        println!("{} {:4} {}", marker, number, operation);
+    }
+    if journal.has_undone() {
+        println!("(operations marked with u are undone and can be redone)");
+    }
+}
+
+pub fn print_history(history: &AuditLog, record: Option<RecordId>) {
+    let mut entries = history.entries(record).peekable();
+    if entries.peek().is_none() {
+        println!("No history recorded.");
+        return;
+    }
+    for entry in entries {
+        println!("{}", entry);
+    }
+}
@@ -20,10 +20,28 @@ pub enum KakeboError {
     TomlSerialization(#[from] toml::ser::Error),
     #[error("Toml Deserialization error: {0}")]
     TomlDeserialization(#[from] toml::de::Error),
+    #[cfg(feature = "interactive")]
     #[error("Inquire error: {0}")]
-    Inquire(#[from] inquire::error::InquireError),
+    Inquire(inquire::error::InquireError),
     #[error("Walkdir error: {0}")]
     Walkdir(#[from] walkdir::Error),
+    #[error("QR code error: {0}")]
+    QrCode(#[from] qrcode::types::QrError),
+    #[error("Image error: {0}")]
+    Image(#[from] image::ImageError),
     #[error("Expense creation aborted")]
     ExpenseCreationAborted,
+    #[error("Prompt canceled")]
+    PromptCanceled,
+}
+
+/// canceling a prompt is not a failure of the terminal, the flows can react to it
+#[cfg(feature = "interactive")]
+impl From<inquire::error::InquireError> for KakeboError {
+    fn from(error: inquire::error::InquireError) -> Self {
+        match error {
+            inquire::error::InquireError::OperationCanceled => KakeboError::PromptCanceled,
+            error => KakeboError::Inquire(error),
+        }
+    }
 }
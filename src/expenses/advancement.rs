@@ -1,34 +1,57 @@
 use std::fmt::Display;
 
-use chrono::{Local, NaiveDate, Weekday};
-use inquire::{DateSelect, Text};
+use chrono::{Local, NaiveDate};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::{errors::KakeboError, DisplayableExpense, Environment, KakeboConfig};
+use crate::audit::{AuditFields, Audited};
+use crate::{errors::KakeboError, prompter::Prompter, Environment};
+use crate::{DisplayableExpense, KakeboConfig};
 
-use super::{money_amount, person};
+use super::{money_amount, person, prompt::description};
+use super::{repayment::Repayments, RecordId};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Advancement {
     pub person: String,
     pub amount: Decimal,
-    description: Option<String>,
-    date: NaiveDate,
+    pub description: Option<String>,
+    pub date: NaiveDate,
     creation_date: NaiveDate,
+    #[serde(default)]
+    pub id: RecordId,
+    #[serde(default)]
+    pub repayments: Repayments,
 }
 
 impl Display for Advancement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.id.is_assigned() {
+            write!(f, "[{}] ", self.id)?;
+        }
         write!(
             f,
-            "{} ({:8.2} owed by {})",
+            "{} (",
             self.description
                 .as_ref()
-                .map_or("No description", |descr| descr.as_str()),
-            self.amount,
-            self.person
-        )
+                .map_or("No description", |descr| descr.as_str())
+        )?;
+        if self.outstanding() != self.amount {
+            write!(f, "{:.2} of ", self.outstanding())?;
+        }
+        write!(f, "{:8.2} owed by {})", self.amount, self.person)
+    }
+}
+
+impl Audited for Advancement {
+    fn audit_fields(&self, fields: &mut AuditFields) {
+        fields.add("owed by", &self.person);
+        fields.add("date", self.date);
+        if let Some(description) = &self.description {
+            fields.add("description", description);
+        }
+        fields.add("amount", format!("{:.2}", self.amount));
+        self.repayments.audit_fields(fields);
     }
 }
 
@@ -40,18 +63,31 @@ impl DisplayableExpense for Advancement {
     fn plural_name() -> &'static str {
         "unpaid advancements"
     }
+
+    fn id(&self) -> RecordId {
+        self.id
+    }
+
+    fn set_id(&mut self, id: RecordId) {
+        self.id = id;
+    }
+
+    fn configured_display(&self, config: &KakeboConfig) -> String {
+        format!("{}{}", self, self.repayments.details(config))
+    }
 }
 
 impl Advancement {
-    pub fn new(environment: &Environment, config: &KakeboConfig) -> Result<Self, KakeboError> {
+    pub fn new(
+        prompter: &mut dyn Prompter,
+        environment: &Environment,
+        config: &KakeboConfig,
+    ) -> Result<Self, KakeboError> {
         let creation_date = Local::now().date_naive();
-        let person = person("Who owes you this money?", &environment.people)?;
-        let date = DateSelect::new("Date:")
-            .with_week_start(Weekday::Mon)
-            .prompt()?;
-        let description = Text::new("Description:").prompt()?;
-        let description = (!description.is_empty()).then_some(description);
-        let amount = money_amount(config, &person)?;
+        let person = person(prompter, "Who owes you this money?", &environment.people)?;
+        let date = prompter.date("Date:")?;
+        let description = description(prompter)?;
+        let amount = money_amount(prompter, config, &person)?;
 
         let new_instance = Self {
             person,
@@ -59,9 +95,16 @@ impl Advancement {
             creation_date,
             date,
             description,
+            id: RecordId::default(),
+            repayments: Repayments::default(),
         };
-        new_instance.configured_display(config);
+        prompter.message(&new_instance.configured_display(config));
 
         Ok(new_instance)
     }
+
+    /// what is still left to be paid back
+    pub fn outstanding(&self) -> Decimal {
+        self.amount - self.repayments.total()
+    }
 }
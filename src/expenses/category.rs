@@ -0,0 +1,344 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::KakeboError;
+
+/// the categories every new database starts with
+const DEFAULT_CATEGORIES: [&str; 8] = [
+    "Replacement or Repair",
+    "Groceries",
+    "Canteen",
+    "Family",
+    "Friends",
+    "Hobby",
+    "Restaurant",
+    "Entertainment",
+];
+
+/// separates a category from its subcategories, e.g. `Food > Groceries`
+pub const SEPARATOR: &str = " > ";
+
+/// trims every level of a category path and joins them with the canonical separator
+pub fn normalize(name: &str) -> String {
+    name.split('>')
+        .map(str::trim)
+        .filter(|level| !level.is_empty())
+        .collect::<Vec<_>>()
+        .join(SEPARATOR)
+}
+
+/// like [`normalize`], but rejects empty names and empty levels such as in `Food > > Snacks`
+pub fn validate(name: &str) -> Result<String, KakeboError> {
+    let levels: Vec<_> = name.split('>').map(str::trim).collect();
+    if levels.iter().any(|level| level.is_empty()) {
+        return Err(KakeboError::InvalidArgument(format!(
+            "\"{}\" is not a valid category, names and their levels must not be empty",
+            name
+        )));
+    }
+    Ok(levels.join(SEPARATOR))
+}
+
+/// if `name` is `from` or one of its subcategories, returns the name after moving it to `to`
+fn moved(name: &str, from: &str, to: &str) -> Option<String> {
+    if name == from {
+        Some(to.to_string())
+    } else {
+        name.strip_prefix(from)
+            .filter(|rest| rest.starts_with(SEPARATOR))
+            .map(|rest| format!("{to}{rest}"))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(from = "StoredCategory")]
+pub struct ExpenseCategory(String);
+
+impl ExpenseCategory {
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+
+    /// the levels of this category from the top-level category down
+    pub fn path(&self) -> impl Iterator<Item = &str> {
+        self.0.split(SEPARATOR)
+    }
+
+    /// whether this category is `ancestor` itself or one of its subcategories
+    pub fn is_within(&self, ancestor: &str) -> bool {
+        moved(&self.0, &normalize(ancestor), "").is_some()
+    }
+
+    pub fn moved(&self, from: &str, to: &str) -> Option<Self> {
+        moved(&self.0, from, to).map(Self)
+    }
+}
+
+impl From<String> for ExpenseCategory {
+    fn from(value: String) -> Self {
+        Self(normalize(&value))
+    }
+}
+
+impl Display for ExpenseCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// the hardcoded categories older databases were written with
+#[derive(Deserialize)]
+enum LegacyCategory {
+    ReplacementOrRepair,
+    Groceries,
+    Canteen,
+    Family,
+    Friends,
+    Hobby,
+    Restaurant,
+    Entertainment,
+    Other(String),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredCategory {
+    Legacy(LegacyCategory),
+    Name(String),
+}
+
+impl From<StoredCategory> for ExpenseCategory {
+    fn from(value: StoredCategory) -> Self {
+        let name = match value {
+            StoredCategory::Legacy(legacy) => match legacy {
+                LegacyCategory::ReplacementOrRepair => "Replacement or Repair".to_string(),
+                LegacyCategory::Groceries => "Groceries".to_string(),
+                LegacyCategory::Canteen => "Canteen".to_string(),
+                LegacyCategory::Family => "Family".to_string(),
+                LegacyCategory::Friends => "Friends".to_string(),
+                LegacyCategory::Hobby => "Hobby".to_string(),
+                LegacyCategory::Restaurant => "Restaurant".to_string(),
+                LegacyCategory::Entertainment => "Entertainment".to_string(),
+                LegacyCategory::Other(inner) => inner,
+            },
+            StoredCategory::Name(name) => name,
+        };
+        Self::from(name)
+    }
+}
+
+/// the user's list of categories in the order they are offered when adding an expense
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Categories(Vec<String>);
+
+impl Default for Categories {
+    fn default() -> Self {
+        Self(DEFAULT_CATEGORIES.iter().map(|s| s.to_string()).collect())
+    }
+}
+
+impl Categories {
+    pub fn names(&self) -> &[String] {
+        &self.0
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.iter().any(|category| category == name)
+    }
+
+    /// whether `name` is a known category or the parent of a known category
+    pub fn covers(&self, name: &str) -> bool {
+        self.0
+            .iter()
+            .any(|category| moved(category, name, "").is_some())
+    }
+
+    fn check_covered(&self, name: &str) -> Result<(), KakeboError> {
+        if !self.covers(name) {
+            return Err(KakeboError::InvalidArgument(format!(
+                "Unknown category {}",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    /// rejects moving `from` to `to` if `to` is `from` itself or one of its subcategories
+    fn check_outside(from: &str, to: &str) -> Result<(), KakeboError> {
+        if moved(to, from, "").is_some() {
+            return Err(KakeboError::InvalidArgument(format!(
+                "Cannot move {} into itself or one of its subcategories",
+                from
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn add(&mut self, name: &str) -> Result<(), KakeboError> {
+        let name = validate(name)?;
+        if self.contains(&name) {
+            return Err(KakeboError::InvalidArgument(format!(
+                "Category {} already exists",
+                name
+            )));
+        }
+        self.0.push(name);
+        Ok(())
+    }
+
+    /// adds the category if it is not known yet, e.g. after the user typed a new one
+    pub fn register(&mut self, category: &ExpenseCategory) {
+        if !self.contains(category.name()) {
+            self.0.push(category.name().to_string());
+        }
+    }
+
+    /// renames a category together with all of its subcategories
+    pub fn rename(&mut self, old: &str, new: &str) -> Result<(), KakeboError> {
+        let new = validate(new)?;
+        self.check_covered(old)?;
+        Self::check_outside(old, &new)?;
+        let renamed: Vec<_> = self
+            .0
+            .iter()
+            .map(|category| moved(category, old, &new).unwrap_or_else(|| category.clone()))
+            .collect();
+        let clashes = renamed
+            .iter()
+            .enumerate()
+            .any(|(i, category)| renamed[..i].contains(category));
+        if clashes {
+            return Err(KakeboError::InvalidArgument(format!(
+                "Category {} already exists, merge the categories instead",
+                new
+            )));
+        }
+        self.0 = renamed;
+        Ok(())
+    }
+
+    /// moves a category and its subcategories below `into`, combining categories that now coincide
+    pub fn merge(&mut self, from: &str, into: &str) -> Result<(), KakeboError> {
+        self.check_covered(from)?;
+        self.check_covered(into)?;
+        Self::check_outside(from, into)?;
+        let mut merged: Vec<String> = Vec::new();
+        for category in &self.0 {
+            let category = moved(category, from, into).unwrap_or_else(|| category.clone());
+            if !merged.contains(&category) {
+                merged.push(category);
+            }
+        }
+        self.0 = merged;
+        Ok(())
+    }
+
+    /// removes a category together with all of its subcategories
+    pub fn remove(&mut self, name: &str) -> Result<(), KakeboError> {
+        self.check_covered(name)?;
+        self.0
+            .retain(|category| moved(category, name, "").is_none());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn categories(names: &[&str]) -> Categories {
+        Categories(names.iter().map(|name| name.to_string()).collect())
+    }
+
+    #[test]
+    fn names_are_normalized() {
+        assert_eq!(
+            normalize(" Food>Groceries >  Fruit "),
+            "Food > Groceries > Fruit"
+        );
+        assert_eq!(validate("Food>Groceries").unwrap(), "Food > Groceries");
+        assert!(validate("").is_err());
+        assert!(validate(" > Groceries").is_err());
+        assert!(validate("Food > > Groceries").is_err());
+    }
+
+    #[test]
+    fn rename_moves_subcategories() {
+        let mut categories = categories(&["Food", "Food > Groceries", "Foods", "Hobby"]);
+        categories.rename("Food", "Meals").unwrap();
+        assert_eq!(
+            categories.names(),
+            ["Meals", "Meals > Groceries", "Foods", "Hobby"]
+        );
+        categories
+            .rename("Meals > Groceries", "Meals > Market")
+            .unwrap();
+        assert_eq!(
+            categories.names(),
+            ["Meals", "Meals > Market", "Foods", "Hobby"]
+        );
+    }
+
+    #[test]
+    fn rename_rejects_empty_unknown_and_existing_names() {
+        let mut categories = categories(&["Food", "Food > Groceries", "Hobby"]);
+        assert!(categories.rename("Food", "").is_err());
+        assert!(categories.rename("Food", "Meals > ").is_err());
+        assert!(categories.rename("Drinks", "Beverages").is_err());
+        assert!(categories.rename("Food", "Hobby").is_err());
+        assert!(categories.rename("Food > Groceries", "Food").is_err());
+        assert_eq!(categories.names(), ["Food", "Food > Groceries", "Hobby"]);
+    }
+
+    #[test]
+    fn parents_of_known_categories_can_be_renamed() {
+        let mut categories = categories(&["Food > Groceries"]);
+        assert!(categories.covers("Food"));
+        categories.rename("Food", "Meals").unwrap();
+        assert_eq!(categories.names(), ["Meals > Groceries"]);
+    }
+
+    #[test]
+    fn is_within_matches_whole_levels() {
+        let category = ExpenseCategory::from("Food > Groceries".to_string());
+        assert!(category.is_within("Food"));
+        assert!(category.is_within("Food>Groceries"));
+        assert!(!category.is_within("Foo"));
+        assert!(!category.is_within("Groceries"));
+    }
+
+    #[test]
+    fn rename_rejects_moving_into_its_own_subtree() {
+        let mut categories = categories(&["Food", "Food > Groceries"]);
+        assert!(categories.rename("Food", "Food > Old").is_err());
+        assert!(categories.rename("Food", "Food").is_err());
+        assert_eq!(categories.names(), ["Food", "Food > Groceries"]);
+    }
+
+    #[test]
+    fn merge_combines_categories_that_coincide() {
+        let mut categories = categories(&[
+            "Groceries",
+            "Groceries > Fruit",
+            "Food",
+            "Food > Fruit",
+            "Hobby",
+        ]);
+        categories.merge("Groceries", "Food").unwrap();
+        assert_eq!(categories.names(), ["Food", "Food > Fruit", "Hobby"]);
+        categories.merge("Food > Fruit", "Hobby").unwrap();
+        assert_eq!(categories.names(), ["Food", "Hobby"]);
+    }
+
+    #[test]
+    fn merge_rejects_targets_inside_the_merged_category() {
+        let mut categories = categories(&["Food", "Food > Groceries", "Hobby"]);
+        assert!(categories.merge("Food", "Food > Groceries").is_err());
+        assert!(categories.merge("Food", "Food").is_err());
+        assert!(categories.merge("Food", "Drinks").is_err());
+        assert_eq!(categories.names(), ["Food", "Food > Groceries", "Hobby"]);
+        categories.merge("Food > Groceries", "Food").unwrap();
+        assert_eq!(categories.names(), ["Food", "Hobby"]);
+    }
+}
@@ -1,29 +1,44 @@
 use std::fmt::Display;
 
+use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::audit::{AuditFields, Audited};
 use crate::errors::KakeboError;
+use crate::prompter::Prompter;
 use crate::DisplayableExpense;
 use crate::Environment;
 use crate::KakeboConfig;
 
 use super::person;
+use super::repayment::Repayments;
 use super::single_expense::SingleExpense;
+use super::RecordId;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Debt {
     pub expense: SingleExpense,
     pub person: String,
+    #[serde(default)]
+    pub repayments: Repayments,
 }
 
 impl Display for Debt {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{} ({:8.2} owed to {})",
-            self.expense.info, self.expense.amount, self.person
-        )
+        write!(f, "{} (", self.expense.info)?;
+        if self.outstanding() != self.expense.amount {
+            write!(f, "{:.2} of ", self.outstanding())?;
+        }
+        write!(f, "{:8.2} owed to {})", self.expense.amount, self.person)
+    }
+}
+
+impl Audited for Debt {
+    fn audit_fields(&self, fields: &mut AuditFields) {
+        fields.add("owed to", &self.person);
+        self.expense.audit_fields(fields);
+        self.repayments.audit_fields(fields);
     }
 }
 
@@ -35,16 +50,45 @@ impl DisplayableExpense for Debt {
     fn plural_name() -> &'static str {
         "debts owed"
     }
+
+    fn id(&self) -> RecordId {
+        self.expense.info.id
+    }
+
+    fn set_id(&mut self, id: RecordId) {
+        self.expense.info.id = id;
+    }
+
+    fn configured_display(&self, config: &KakeboConfig) -> String {
+        format!("{}{}", self, self.repayments.details(config))
+    }
 }
 
 impl Debt {
-    pub fn new(environment: &Environment, config: &KakeboConfig) -> Result<Self, KakeboError> {
-        let person = person("Who do you owe this money to?", &environment.people)?;
-        let expense = SingleExpense::new(config)?;
+    pub fn new(
+        prompter: &mut dyn Prompter,
+        environment: &Environment,
+        config: &KakeboConfig,
+    ) -> Result<Self, KakeboError> {
+        let person = person(
+            prompter,
+            "Who do you owe this money to?",
+            &environment.people,
+        )?;
+        let expense = SingleExpense::new(prompter, environment, config)?;
 
-        let new_instance = Self { expense, person };
-        new_instance.configured_display(config);
+        let new_instance = Self {
+            expense,
+            person,
+            repayments: Repayments::default(),
+        };
+        prompter.message(&new_instance.configured_display(config));
 
         Ok(new_instance)
     }
+
+    /// what is still left to pay back
+    pub fn outstanding(&self) -> Decimal {
+        self.expense.amount - self.repayments.total()
+    }
 }
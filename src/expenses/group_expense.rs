@@ -1,15 +1,20 @@
-use std::{collections::HashMap, fmt::Display};
+use std::collections::HashMap;
+use std::{cmp::Reverse, fmt::Display};
 
-use inquire::{Confirm, InquireError, Select};
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    errors::KakeboError, DisplayableExpense, Environment, KakeboConfig, ANSI_GREEN, ANSI_RED,
-    ANSI_STOP,
+    audit::{AuditFields, Audited},
+    errors::KakeboError,
+    prompter::{ask, choose, choose_many, Prompter, Question},
+    Environment,
 };
+use crate::{DisplayableExpense, KakeboConfig, ANSI_GREEN, ANSI_RED, ANSI_STOP};
 
-use super::{money_amount, person, ExpenseInfo, NEW_PERSON};
+use super::prompt::{parse_limited_amount, NEW_PERSON};
+use super::{money_amount, person, repayment::Repayment};
+use super::{repayment::Repayments, ExpenseInfo, RecordId};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct GroupExpensePart {
@@ -33,8 +38,255 @@ impl Display for GroupExpensePart {
     }
 }
 
+/// how the total of a group expense is divided between the people
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SplitMode {
+    /// raw amounts that are scaled to the total, e.g. prices before tips or delivery fees
+    #[default]
+    Scaled,
+    Equal,
+    Percentage,
+    /// weighted shares, e.g. by room size
+    Shares,
+    /// exact amounts that add up to the total
+    Exact,
+    /// line items of a receipt, each shared evenly by the people who had it
+    Itemised,
+}
+
+impl Display for SplitMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            SplitMode::Scaled => "scaled raw amounts",
+            SplitMode::Equal => "equal",
+            SplitMode::Percentage => "by percentage",
+            SplitMode::Shares => "by shares",
+            SplitMode::Exact => "exact amounts",
+            SplitMode::Itemised => "by receipt items",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+impl SplitMode {
+    const ALL: [SplitMode; 6] = [
+        SplitMode::Scaled,
+        SplitMode::Equal,
+        SplitMode::Percentage,
+        SplitMode::Shares,
+        SplitMode::Exact,
+        SplitMode::Itemised,
+    ];
+
+    /// asks for the raw value of one person, which is scaled to the total afterwards
+    ///
+    /// Itemised raw values start at zero and are filled in from the receipt items.
+    fn raw_amount(
+        &self,
+        prompter: &mut dyn Prompter,
+        config: &KakeboConfig,
+        name: &str,
+    ) -> Result<Decimal, KakeboError> {
+        let unit = match self {
+            SplitMode::Equal => return Ok(Decimal::ONE),
+            SplitMode::Itemised => return Ok(Decimal::ZERO),
+            SplitMode::Scaled => return money_amount(prompter, config, &format!("{} (raw)", name)),
+            SplitMode::Exact => return money_amount(prompter, config, name),
+            SplitMode::Percentage => "%",
+            SplitMode::Shares => "shares",
+        };
+        let message = format!("{} ({}):", name, unit);
+        ask(prompter, Question::new(&message), |input| {
+            match input.trim().parse::<Decimal>() {
+                Ok(value) if value > Decimal::ZERO => Ok(value),
+                Ok(_) => Err("Value must be positive (non-zero).".to_string()),
+                Err(_) => Err("Please type a valid number".to_string()),
+            }
+        })
+    }
+
+    /// how a raw value is shown next to the person, if it means more than the resulting amount
+    fn describe(&self, raw_amount: Decimal) -> Option<String> {
+        match self {
+            SplitMode::Percentage => Some(format!("{}%", raw_amount.normalize())),
+            SplitMode::Shares => Some(format!("{} shares", raw_amount.normalize())),
+            _ => None,
+        }
+    }
+}
+
+/// a line of a receipt and the people who shared it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReceiptItem {
+    pub description: String,
+    pub price: Decimal,
+    pub consumers: Vec<String>,
+}
+
+impl Display for ReceiptItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:20} {:8.2} ({})",
+            self.description,
+            self.price,
+            self.consumers.join(", ")
+        )
+    }
+}
+
+impl ReceiptItem {
+    /// asks for line items until the user is done, `participants` are the people who can share them
+    fn prompt_all(
+        prompter: &mut dyn Prompter,
+        config: &KakeboConfig,
+        participants: &[String],
+    ) -> Result<Vec<Self>, KakeboError> {
+        let mut items = Vec::new();
+        loop {
+            let question = Question::new("Item:").with_help("Press ESC when all items are added");
+            let description = match prompter.input(&question) {
+                Err(KakeboError::PromptCanceled) => break,
+                description => description?,
+            };
+            let price = money_amount(prompter, config, &description)?;
+            let consumers = choose_many(prompter, "Who shared it?", participants.to_vec(), 1)?;
+            items.push(Self {
+                description,
+                price,
+                consumers,
+            });
+        }
+        Ok(items)
+    }
+
+    /// the part of the price each consumer pays before tips and fees
+    fn price_per_consumer(&self) -> Decimal {
+        self.price / Decimal::from(self.consumers.len())
+    }
+}
+
+/// how an extra cost such as a tip or a delivery fee is distributed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ExtraPolicy {
+    /// in proportion to the raw amounts
+    Proportional,
+    Equal,
+    /// covered completely by one person
+    Assigned(String),
+}
+
+impl Display for ExtraPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtraPolicy::Proportional => write!(f, "proportionally"),
+            ExtraPolicy::Equal => write!(f, "equally"),
+            ExtraPolicy::Assigned(person) => write!(f, "covered by {}", person),
+        }
+    }
+}
+
+/// a named part of the difference between the raw amounts and the total
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Extra {
+    pub name: String,
+    pub amount: Decimal,
+    pub policy: ExtraPolicy,
+}
+
+impl Display for Extra {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:20} {:8.2} ({})", self.name, self.amount, self.policy)
+    }
+}
+
+impl Extra {
+    /// asks for extras until `difference` is covered or the user is done,
+    /// whatever is left becomes a proportional extra
+    fn prompt_all(
+        prompter: &mut dyn Prompter,
+        config: &KakeboConfig,
+        participants: &[String],
+        difference: Decimal,
+    ) -> Result<Vec<Self>, KakeboError> {
+        let mut extras = Vec::new();
+        let mut remaining = difference;
+        while remaining > Decimal::ZERO {
+            prompter.message(&format!(
+                "{:.2}{} of the total are extra costs",
+                remaining, config.currency
+            ));
+            let question = Question::new("Extra cost:")
+                .with_help("e.g. Tip or Delivery fee, press ESC to split the rest proportionally");
+            let name = match prompter.input(&question) {
+                Err(KakeboError::PromptCanceled) => break,
+                name => name?,
+            };
+            let message = format!("Amount {}:", name);
+            let amount = ask(
+                prompter,
+                Question::new(&message).with_default(remaining),
+                move |input| parse_limited_amount(input, remaining, "is left"),
+            )?;
+            let policies: Vec<_> = [ExtraPolicy::Proportional, ExtraPolicy::Equal]
+                .into_iter()
+                .chain(participants.iter().cloned().map(ExtraPolicy::Assigned))
+                .collect();
+            let policy = choose(prompter, "How is it distributed?", policies)?;
+            remaining -= amount;
+            extras.push(Self {
+                name,
+                amount,
+                policy,
+            });
+        }
+        if remaining > Decimal::ZERO {
+            extras.push(Self {
+                name: "Other".to_string(),
+                amount: remaining,
+                policy: ExtraPolicy::Proportional,
+            });
+        }
+        Ok(extras)
+    }
+}
+
+/// scales the raw amounts to the total in cents, handing the cents lost to rounding down
+/// to the amounts with the largest remainders so the result always adds up to the total
+///
+/// A zero raw total, which only older records can have, splits the total equally.
+fn split_exactly(raw_amounts: &[Decimal], total: Decimal) -> Vec<Decimal> {
+    let raw_total: Decimal = raw_amounts.iter().sum();
+    let exact: Vec<_> = if raw_total.is_zero() {
+        let count = Decimal::from(raw_amounts.len());
+        raw_amounts.iter().map(|_| total / count).collect()
+    } else {
+        raw_amounts
+            .iter()
+            .map(|raw_amount| raw_amount * total / raw_total)
+            .collect()
+    };
+    let mut shares: Vec<_> = exact
+        .iter()
+        .map(|amount| amount.round_dp_with_strategy(2, RoundingStrategy::ToNegativeInfinity))
+        .collect();
+    let mut by_remainder: Vec<_> = (0..shares.len()).collect();
+    by_remainder.sort_by_key(|&i| Reverse(exact[i] - shares[i]));
+    let cent = Decimal::new(1, 2);
+    let mut missing = total.round_dp(2) - shares.iter().sum::<Decimal>();
+    // every share lost less than a cent, so each one gets at most one of the missing cents
+    for i in by_remainder {
+        if missing < cent {
+            break;
+        }
+        shares[i] += cent;
+        missing -= cent;
+    }
+    shares
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
-/// a group expense that distributes extra costs such as tips or delivery fees fairly
+/// a group expense that distributes extra costs such as tips or delivery fees by their policies
 pub struct GroupExpense {
     pub info: ExpenseInfo,
     raw_user_amount: Decimal,
@@ -42,11 +294,35 @@ pub struct GroupExpense {
     raw_amounts: Vec<Decimal>,
     total_amount: Decimal,
     pub paid_amounts: Vec<Option<Decimal>>,
+    /// who paid the bill, `None` if the user did
+    #[serde(default)]
+    pub payer: Option<String>,
+    /// what the user paid back to the payer for their own share
+    #[serde(default)]
+    pub repayments: Repayments,
+    #[serde(default)]
+    pub split: SplitMode,
+    #[serde(default)]
+    pub items: Vec<ReceiptItem>,
+    #[serde(default)]
+    pub extras: Vec<Extra>,
 }
 
 impl Display for GroupExpense {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} (Total: {:8.2})", self.info, self.total_amount)
+        write!(f, "{} (Total: {:8.2}", self.info, self.total_amount)?;
+        if let Some(payer) = &self.payer {
+            write!(f, ", paid by {}", payer)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Audited for GroupExpense {
+    fn audit_fields(&self, fields: &mut AuditFields) {
+        self.info.audit_fields(fields);
+        fields.add("total", format!("{:.2}", self.total_amount));
+        self.audit_split(fields);
     }
 }
 
@@ -59,28 +335,97 @@ impl DisplayableExpense for GroupExpense {
         "group expenses"
     }
 
-    fn configured_display(&self, config: &KakeboConfig) {
-        self.print(config)
+    fn id(&self) -> RecordId {
+        self.info.id
+    }
+
+    fn set_id(&mut self, id: RecordId) {
+        self.info.id = id;
+    }
+
+    fn configured_display(&self, config: &KakeboConfig) -> String {
+        self.details(config)
     }
 }
 
 impl GroupExpense {
-    pub fn raw_total(&self) -> Decimal {
-        self.raw_amounts.iter().sum::<Decimal>() + self.raw_user_amount
+    pub fn total_amount(&self) -> Decimal {
+        self.total_amount
+    }
+
+    /// the user's share followed by the shares of the other people, adding up to the total
+    fn shares(&self) -> Vec<Decimal> {
+        self.shares_of(self.total_amount)
     }
 
-    fn true_amount(&self, raw_amount: Decimal) -> Decimal {
-        let true_amount_unscaled = raw_amount * self.total_amount / self.raw_total();
-        true_amount_unscaled.round_dp(2)
+    /// what the user's share would be if the expense had a different total
+    pub fn user_share_of(&self, total_amount: Decimal) -> Decimal {
+        self.shares_of(total_amount)[0]
+    }
+
+    fn shares_of(&self, total_amount: Decimal) -> Vec<Decimal> {
+        let raw_amounts: Vec<_> = std::iter::once(self.raw_user_amount)
+            .chain(self.raw_amounts.iter().copied())
+            .collect();
+        let raw_total: Decimal = raw_amounts.iter().sum();
+        let mut weights = raw_amounts.clone();
+        for extra in &self.extras {
+            match &extra.policy {
+                ExtraPolicy::Proportional if !raw_total.is_zero() => {
+                    for (weight, raw_amount) in weights.iter_mut().zip(&raw_amounts) {
+                        *weight += extra.amount * raw_amount / raw_total;
+                    }
+                }
+                ExtraPolicy::Proportional | ExtraPolicy::Equal => {
+                    let count = Decimal::from(weights.len());
+                    for weight in weights.iter_mut() {
+                        *weight += extra.amount / count;
+                    }
+                }
+                ExtraPolicy::Assigned(person) => {
+                    // the user is stored by name as well, but is not one of the people
+                    let i = self
+                        .people
+                        .iter()
+                        .position(|other| other == person)
+                        .map_or(0, |i| i + 1);
+                    weights[i] += extra.amount;
+                }
+            }
+        }
+        // a rest that is not covered by extras, e.g. in older records or a higher total of a
+        // recurring occurrence, is distributed like a proportional extra
+        let rest = total_amount - weights.iter().sum::<Decimal>();
+        if rest > Decimal::ZERO && !raw_total.is_zero() {
+            for (weight, raw_amount) in weights.iter_mut().zip(&raw_amounts) {
+                *weight += rest * raw_amount / raw_total;
+            }
+        }
+        split_exactly(&weights, total_amount)
     }
 
     pub fn true_user_amount(&self) -> Decimal {
-        self.true_amount(self.raw_user_amount)
+        self.shares()[0]
+    }
+
+    pub fn paid_by_user(&self) -> bool {
+        self.payer.is_none()
+    }
+
+    /// what the user still owes the payer for their own share
+    pub fn user_outstanding(&self) -> Decimal {
+        if self.paid_by_user() {
+            Decimal::ZERO
+        } else {
+            self.true_user_amount() - self.repayments.total()
+        }
     }
 
+    /// the parts the other people owe the user, empty if someone else paid the bill
     pub fn parts(&self) -> impl Iterator<Item = GroupExpensePart> + use<'_> {
         self.people
             .iter()
+            .filter(|_| self.paid_by_user())
             .zip(self.true_amounts())
             .zip(&self.paid_amounts)
             .enumerate()
@@ -94,14 +439,44 @@ impl GroupExpense {
     }
 
     pub fn true_amounts(&self) -> Vec<Decimal> {
-        self.raw_amounts
-            .iter()
-            .map(|&raw_amount| self.true_amount(raw_amount))
-            .collect()
+        self.shares().split_off(1)
     }
 
-    pub fn print(&self, config: &KakeboConfig) {
-        println!("{}", self);
+    /// the expense with the split and what everybody still has to pay
+    pub fn details(&self, config: &KakeboConfig) -> String {
+        let mut details = self.to_string();
+        let currency = config.currency;
+        if let Some(payer) = &self.payer {
+            details.push_str(&format!(
+                "\n  {:10} {ANSI_RED}{:8.2}{currency}{ANSI_STOP} (to pay: {:8.2}{currency}, paid: {:8.2}{currency}) to {}",
+                config.user_name,
+                self.user_outstanding(),
+                self.true_user_amount(),
+                self.repayments.total(),
+                payer,
+            ));
+            details.push_str(&self.repayments.details(config));
+            return details;
+        }
+        let raw_descriptions: Option<Vec<_>> =
+            std::iter::once((&config.user_name, &self.raw_user_amount))
+                .chain(self.people.iter().zip(&self.raw_amounts))
+                .map(|(person, &raw_amount)| {
+                    self.split
+                        .describe(raw_amount)
+                        .map(|description| format!("{} {}", person, description))
+                })
+                .collect();
+        details.push_str(&match raw_descriptions {
+            Some(descriptions) => format!("\n  Split {} ({})", self.split, descriptions.join(", ")),
+            None => format!("\n  Split {}", self.split),
+        });
+        for item in &self.items {
+            details.push_str(&format!("\n    {}", item));
+        }
+        for extra in &self.extras {
+            details.push_str(&format!("\n    {}", extra));
+        }
         for (person, (needed, paid)) in self.people.iter().zip(
             self.true_amounts()
                 .into_iter()
@@ -114,78 +489,219 @@ impl GroupExpense {
             } else {
                 ANSI_RED
             };
-            println!(
-                "  {:10} {to_pay_colour}{:8.2}{currency}{ANSI_STOP} (to pay: {:8.2}{currency}, paid: {:8.2}{currency})",
-                person, to_pay, needed, paid, currency=config.currency
+            details.push_str(&format!(
+                "\n  {:10} {to_pay_colour}{:8.2}{currency}{ANSI_STOP} (to pay: {:8.2}{currency}, paid: {:8.2}{currency})",
+                person, to_pay, needed, paid
+            ));
+        }
+        details
+    }
+
+    /// the fields of the split and what was paid, without the expense itself
+    pub(super) fn audit_split(&self, fields: &mut AuditFields) {
+        fields.add("split", self.split);
+        if let Some(payer) = &self.payer {
+            fields.add("paid by", payer);
+        }
+        let shares = self.shares();
+        fields.add("your raw amount", self.raw_user_amount.normalize());
+        fields.add("your share", format!("{:.2}", shares[0]));
+        for (i, person) in self.people.iter().enumerate() {
+            fields.add(
+                format!("{} raw amount", person),
+                self.raw_amounts[i].normalize(),
             );
+            fields.add(format!("{} share", person), format!("{:.2}", shares[i + 1]));
+            if let Some(paid) = self.paid_amounts[i] {
+                fields.add(format!("{} paid", person), format!("{:.2}", paid));
+            }
         }
+        for (i, item) in self.items.iter().enumerate() {
+            fields.add(
+                format!("item {}", i + 1),
+                format!(
+                    "{} {:.2} ({})",
+                    item.description,
+                    item.price,
+                    item.consumers.join(", ")
+                ),
+            );
+        }
+        for (i, extra) in self.extras.iter().enumerate() {
+            fields.add(
+                format!("extra {}", i + 1),
+                format!("{} {:.2} ({})", extra.name, extra.amount, extra.policy),
+            );
+        }
+        self.repayments.audit_fields(fields);
     }
 
-    pub fn new(environment: &Environment, config: &KakeboConfig) -> Result<Self, KakeboError> {
-        let info = ExpenseInfo::new()?;
-        let raw_user_amount = money_amount(config, &format!("{} (raw)", &config.user_name))?;
+    /// a copy of this split for another expense that nobody has paid for yet
+    pub fn instantiate(&self, info: ExpenseInfo, total_amount: Decimal) -> Self {
+        Self {
+            info,
+            raw_user_amount: self.raw_user_amount,
+            people: self.people.clone(),
+            raw_amounts: self.raw_amounts.clone(),
+            total_amount,
+            paid_amounts: vec![None; self.people.len()],
+            payer: self.payer.clone(),
+            repayments: Repayments::default(),
+            split: self.split,
+            items: self.items.clone(),
+            extras: self.extras.clone(),
+        }
+    }
+}
+
+impl GroupExpense {
+    pub fn new(
+        prompter: &mut dyn Prompter,
+        environment: &Environment,
+        config: &KakeboConfig,
+    ) -> Result<Self, KakeboError> {
+        let info = ExpenseInfo::new(prompter, environment)?;
+        let mut new_instance = Self::split(prompter, info, environment, config)?;
+
+        let mut need_to_pay: HashMap<String, usize> = new_instance
+            .people
+            .iter()
+            .enumerate()
+            .map(|(i, person)| (person.clone(), i))
+            .collect();
+
+        while new_instance.payer.is_none() && !need_to_pay.is_empty() {
+            let options: Vec<String> = need_to_pay.keys().map(String::clone).collect();
+            let person_that_paid = match choose(prompter, "Who already payed?", options) {
+                Err(KakeboError::PromptCanceled) => break,
+                person_that_paid => person_that_paid?,
+            };
+            let paid_amount =
+                money_amount(prompter, config, &format!("{} (paid)", person_that_paid))?;
+            let index = need_to_pay
+                .remove_entry(&person_that_paid)
+                .ok_or_else(|| KakeboError::InvalidArgument(person_that_paid.clone()))?
+                .1;
+            new_instance.paid_amounts[index] = Some(paid_amount);
+        }
+        prompter.message(&new_instance.configured_display(config));
+
+        if prompter.confirm("Save this expense?")? {
+            Ok(new_instance)
+        } else {
+            Err(KakeboError::ExpenseCreationAborted)
+        }
+    }
+
+    /// asks how the expense is split and who paid it, nobody has paid their part yet
+    pub fn split(
+        prompter: &mut dyn Prompter,
+        info: ExpenseInfo,
+        environment: &Environment,
+        config: &KakeboConfig,
+    ) -> Result<Self, KakeboError> {
+        let split = choose(
+            prompter,
+            "How is the expense split?",
+            SplitMode::ALL.to_vec(),
+        )?;
+        let mut raw_user_amount = split.raw_amount(prompter, config, &config.user_name)?;
         let mut people = Vec::new();
         let mut raw_amounts = Vec::new();
 
         let mut people_still_possible = environment.people.clone();
 
         loop {
-            let person_result = person("Add person:", &people_still_possible);
-            if let Err(InquireError::OperationCanceled) = person_result {
-                break;
-            }
-            let person_name = person_result?;
+            let person_name = match person(prompter, "Add person:", &people_still_possible) {
+                Err(KakeboError::PromptCanceled) => break,
+                person_name => person_name?,
+            };
             if person_name == NEW_PERSON {
                 continue;
             }
-            let person_amount = money_amount(config, &format!("{} (raw)", person_name))?;
+            let person_amount = split.raw_amount(prompter, config, &person_name)?;
             people_still_possible.remove(&person_name);
             people.push(person_name);
             raw_amounts.push(person_amount);
         }
-        let total_amount = money_amount(config, "total")?;
 
-        let mut paid_amounts = vec![None; people.len()];
-        let mut need_to_pay: HashMap<String, usize> = people
-            .iter()
-            .enumerate()
-            .map(|(i, person)| (person.clone(), i))
+        let participants: Vec<_> = std::iter::once(&config.user_name)
+            .chain(people.iter())
+            .cloned()
             .collect();
-
-        while !need_to_pay.is_empty() {
-            let options: Vec<String> = need_to_pay.keys().map(String::clone).collect();
-            let person_that_paid = Select::new("Who already payed?", options).prompt();
-            if let Err(InquireError::OperationCanceled) = person_that_paid {
-                break;
+        let items = if split == SplitMode::Itemised {
+            ReceiptItem::prompt_all(prompter, config, &participants)?
+        } else {
+            Vec::new()
+        };
+        for item in &items {
+            for consumer in &item.consumers {
+                match people.iter().position(|person| person == consumer) {
+                    Some(i) => raw_amounts[i] += item.price_per_consumer(),
+                    None => raw_user_amount += item.price_per_consumer(),
+                }
             }
-            let person_that_paid = person_that_paid?;
-            let paid_amount = money_amount(config, &format!("{} (paid)", person_that_paid))?;
-            let index = need_to_pay
-                .remove_entry(&person_that_paid)
-                .ok_or_else(|| KakeboError::InvalidArgument(person_that_paid.clone()))?
-                .1;
-            paid_amounts[index] = Some(paid_amount);
         }
+        let raw_total = raw_amounts.iter().sum::<Decimal>() + raw_user_amount;
+        if raw_total.is_zero() {
+            return Err(KakeboError::InvalidArgument(
+                "The raw amounts must not add up to zero".to_string(),
+            ));
+        }
+        let total_amount = match split {
+            SplitMode::Exact => raw_total,
+            SplitMode::Percentage if raw_total != Decimal::ONE_HUNDRED => {
+                return Err(KakeboError::InvalidArgument(format!(
+                    "The percentages add up to {}% instead of 100%",
+                    raw_total
+                )));
+            }
+            _ => money_amount(prompter, config, "total")?,
+        };
 
-        let new_instance = Self {
+        let extras = match split {
+            SplitMode::Scaled | SplitMode::Itemised => {
+                Extra::prompt_all(prompter, config, &participants, total_amount - raw_total)?
+            }
+            _ => Vec::new(),
+        };
+
+        let payer = choose(prompter, "Who paid the bill?", participants)?;
+        let payer = (payer != config.user_name).then_some(payer);
+
+        Ok(Self {
             info,
             raw_user_amount,
+            paid_amounts: vec![None; people.len()],
             people,
             raw_amounts,
             total_amount,
-            paid_amounts,
-        };
-        new_instance.configured_display(config);
-
-        if Confirm::new("Save this expense?").prompt()? {
-            Ok(new_instance)
-        } else {
-            Err(KakeboError::ExpenseCreationAborted)
-        }
+            payer,
+            repayments: Repayments::default(),
+            split,
+            items,
+            extras,
+        })
     }
 
-    pub fn edit(&mut self, config: &KakeboConfig) -> Result<bool, KakeboError> {
-        self.print(config);
+    pub fn edit(
+        &mut self,
+        prompter: &mut dyn Prompter,
+        config: &KakeboConfig,
+    ) -> Result<bool, KakeboError> {
+        prompter.message(&self.details(config));
+
+        if !self.paid_by_user() {
+            if self.user_outstanding() <= Decimal::ZERO {
+                return Ok(false);
+            }
+            let repayment = match Repayment::new(prompter, self.user_outstanding()) {
+                Err(KakeboError::PromptCanceled) => return Ok(false),
+                repayment => repayment?,
+            };
+            self.repayments.push(repayment);
+            return Ok(true);
+        }
 
         let to_pay = self
             .true_amounts()
@@ -210,12 +726,12 @@ impl GroupExpense {
         let mut changes_made = false;
         while !need_to_pay.is_empty() {
             let options: Vec<String> = need_to_pay.keys().map(|&name| name.clone()).collect();
-            let person_that_paid = Select::new("Who already payed?", options).prompt();
-            if let Err(InquireError::OperationCanceled) = person_that_paid {
-                return Ok(changes_made);
-            }
-            let person_that_paid = person_that_paid?;
-            let paid_amount = money_amount(config, &format!("{} (paid)", person_that_paid))?;
+            let person_that_paid = match choose(prompter, "Who already payed?", options) {
+                Err(KakeboError::PromptCanceled) => return Ok(changes_made),
+                person_that_paid => person_that_paid?,
+            };
+            let paid_amount =
+                money_amount(prompter, config, &format!("{} (paid)", person_that_paid))?;
             let index = need_to_pay
                 .remove_entry(&person_that_paid)
                 .ok_or_else(|| KakeboError::InvalidArgument(person_that_paid.clone()))?
@@ -227,3 +743,206 @@ impl GroupExpense {
         Ok(changes_made)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::prompter::ScriptedPrompter;
+    use crate::testing::{add_group, amount};
+    use crate::Expenses;
+
+    use super::*;
+
+    fn environment() -> Environment {
+        Environment {
+            people: ["Anna".to_string(), "Bob".to_string()].into(),
+            tags: Default::default(),
+            categories: vec!["Food".to_string()],
+        }
+    }
+
+    fn group_expense(split_answers: &[&str]) -> GroupExpense {
+        let config = KakeboConfig::default();
+        let answers = ["2026-03-01", "Food", "Dinner", ""]
+            .iter()
+            .chain(split_answers)
+            // the user paid the bill, nobody paid their part yet
+            .chain(&["Friedrich", "<esc>", "y"]);
+        let mut prompter = ScriptedPrompter::new(answers.copied());
+        GroupExpense::new(&mut prompter, &environment(), &config).unwrap()
+    }
+
+    #[test]
+    fn percentages_and_shares_divide_the_total() {
+        let expense = group_expense(&["by percentage", "40", "Anna", "60", "<esc>", "50"]);
+        assert_eq!(expense.true_user_amount(), amount("20"));
+        assert_eq!(expense.true_amounts(), [amount("30")]);
+
+        let expense = group_expense(&["by shares", "1", "Anna", "3", "Bob", "1", "<esc>", "50"]);
+        assert_eq!(expense.true_user_amount(), amount("10"));
+        assert_eq!(expense.true_amounts(), [amount("30"), amount("10")]);
+    }
+
+    #[test]
+    fn percentages_must_add_up_to_one_hundred() {
+        let config = KakeboConfig::default();
+        let mut prompter = ScriptedPrompter::new([
+            "2026-03-01",
+            "Food",
+            "",
+            "",
+            "by percentage",
+            "40",
+            "Anna",
+            "50",
+            "<esc>",
+        ]);
+        assert!(matches!(
+            GroupExpense::new(&mut prompter, &environment(), &config),
+            Err(KakeboError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn equal_split_adds_up_to_the_total() {
+        let expense = group_expense(&["equal", "Anna", "Bob", "<esc>", "10"]);
+        assert_eq!(expense.true_user_amount(), Decimal::new(334, 2));
+        assert_eq!(
+            expense.true_amounts(),
+            [Decimal::new(333, 2), Decimal::new(333, 2)]
+        );
+    }
+
+    #[test]
+    fn itemised_split_shares_items_evenly() {
+        let expense = group_expense(&[
+            "by receipt items",
+            "Anna",
+            "<esc>",
+            "Pizza",
+            "12",
+            "Friedrich,Anna",
+            "Wine",
+            "8",
+            "Anna",
+            "<esc>",
+            "20",
+        ]);
+        assert_eq!(expense.items.len(), 2);
+        assert!(expense.extras.is_empty());
+        assert_eq!(expense.true_user_amount(), amount("6"));
+        assert_eq!(expense.true_amounts(), [amount("14")]);
+    }
+
+    #[test]
+    fn itemised_split_with_extras() {
+        let expense = group_expense(&[
+            "by receipt items",
+            "Anna",
+            "<esc>",
+            "Pizza",
+            "12",
+            "Friedrich,Anna",
+            "Wine",
+            "8",
+            "Anna",
+            "<esc>",
+            "23",
+            "Tip",
+            "2",
+            "equally",
+            "<esc>",
+        ]);
+        let names: Vec<_> = expense
+            .extras
+            .iter()
+            .map(|extra| extra.name.as_str())
+            .collect();
+        assert_eq!(names, ["Tip", "Other"]);
+        // raw 6/14, the tip 1/1 and the other 1.00 in proportion to the raw amounts
+        assert_eq!(expense.true_user_amount(), Decimal::new(730, 2));
+        assert_eq!(expense.true_amounts(), [Decimal::new(1570, 2)]);
+    }
+
+    #[test]
+    fn unnamed_extras_follow_the_raw_amounts() {
+        let expense = group_expense(&[
+            "scaled raw amounts",
+            "10",
+            "Bob",
+            "10",
+            "<esc>",
+            "30",
+            "Tip",
+            "5",
+            "covered by Bob",
+            "<esc>",
+        ]);
+        assert_eq!(expense.true_user_amount(), Decimal::new(1250, 2));
+        assert_eq!(expense.true_amounts(), [Decimal::new(1750, 2)]);
+    }
+
+    #[test]
+    fn a_bill_paid_by_someone_else_is_owed_to_the_payer() {
+        let mut expenses = Expenses::default();
+        add_group(
+            &mut expenses,
+            "2026-03-01",
+            "Dinner",
+            "10",
+            &[("Anna", "12"), ("Bob", "8")],
+            Some("Anna"),
+        );
+        let dinner = &expenses.group_expenses[0];
+        assert_eq!(dinner.parts().count(), 0);
+        assert_eq!(dinner.user_outstanding(), amount("10"));
+
+        // what Bob owes Anna is none of the user's business
+        add_group(
+            &mut expenses,
+            "2026-03-02",
+            "Lunch",
+            "5",
+            &[("Anna", "4")],
+            None,
+        );
+        let balances: Vec<_> = expenses
+            .ledgers()
+            .into_iter()
+            .map(|ledger| (ledger.person.clone(), ledger.balance()))
+            .collect();
+        assert_eq!(
+            balances,
+            [
+                ("Anna".to_string(), amount("-6")),
+                ("Bob".to_string(), Decimal::ZERO)
+            ]
+        );
+    }
+
+    #[test]
+    fn the_share_is_paid_back_to_the_payer() {
+        let mut expenses = Expenses::default();
+        add_group(
+            &mut expenses,
+            "2026-03-01",
+            "Dinner",
+            "10",
+            &[("Anna", "20")],
+            Some("Anna"),
+        );
+        let config = KakeboConfig::default();
+        let dinner = &mut expenses.group_expenses[0];
+
+        let mut prompter = ScriptedPrompter::new(["2026-03-05", "4"]);
+        assert!(dinner.edit(&mut prompter, &config).unwrap());
+        assert_eq!(dinner.user_outstanding(), amount("6"));
+        let mut prompter = ScriptedPrompter::new(["2026-03-06", "6"]);
+        assert!(dinner.edit(&mut prompter, &config).unwrap());
+        assert_eq!(dinner.user_outstanding(), Decimal::ZERO);
+
+        // there is nothing left to pay back
+        let mut prompter = ScriptedPrompter::new::<_, &str>([]);
+        assert!(!dinner.edit(&mut prompter, &config).unwrap());
+        assert_eq!(expenses.ledger("Anna").balance(), Decimal::ZERO);
+    }
+}
@@ -1,104 +1,113 @@
-use std::{collections::BTreeSet, fmt::Display, iter::once};
+use std::{collections::BTreeSet, fmt::Display, num::ParseIntError, str::FromStr};
 
-use chrono::{Local, NaiveDate, Weekday};
-use inquire::{
-    error::InquireResult, required, validator::Validation, CustomType, DateSelect, Select, Text,
-};
-use rust_decimal::Decimal;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
-use crate::{errors::KakeboError, KakeboConfig};
+use crate::audit::{AuditFields, Audited};
+
+use category::ExpenseCategory;
 
 pub mod advancement;
+pub mod category;
 pub mod debt;
 pub mod group_expense;
+pub mod prompt;
 pub mod recurring_expense;
+pub mod repayment;
+pub mod rrule;
 pub mod single_expense;
 
-pub fn money_amount(config: &KakeboConfig, name: &str) -> InquireResult<Decimal> {
-    CustomType::new(&format!("Amount {name}:"))
-        .with_parser(&|input: &str| {
-            let mut sum = Decimal::ZERO;
-            for part in input.split('+') {
-                let part = part.trim();
-                let parse_result = part.parse::<Decimal>();
-                let parsed_decimal = parse_result.map_err(|_| ())?;
-                let checked_sum = sum.checked_add(parsed_decimal);
-                sum = checked_sum.ok_or(())?;
-            }
-            Ok(sum)
-        })
-        .with_validator(|&input: &Decimal| {
-            if input > Decimal::ZERO {
-                Ok(Validation::Valid)
-            } else {
-                Ok(Validation::Invalid(
-                    "Amount must be positive (non-zero).".into(),
-                ))
-            }
-        })
-        .with_formatter(&|decimal: Decimal| format!("{:.2}{}", decimal, config.currency))
-        .with_error_message("Please type a valid number or sum of valid numbers")
-        .with_help_message(&format!(
-            "Type the amount in {} using a decimal point as a separator",
-            config.currency
-        ))
-        .prompt()
-}
+pub use prompt::{category, money_amount, person, tags};
 
-const NEW_PERSON: &str = "Add new Person";
+/// a short identifier that stays the same for the whole lifetime of a record
+///
+/// Records of older databases are read with the unassigned ID `0` and get a real one on load.
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct RecordId(u64);
+
+impl RecordId {
+    pub fn is_assigned(&self) -> bool {
+        self.0 != 0
+    }
 
-pub fn person(prompt: &str, people: &BTreeSet<String>) -> InquireResult<String> {
-    if people.is_empty() {
-        return Text::new(prompt).prompt();
+    pub fn next(&self) -> Self {
+        Self(self.0 + 1)
     }
-    let options_vec: Vec<_> = once(NEW_PERSON)
-        .chain(people.iter().map(String::as_str))
-        .collect();
-    let selected = Select::new(prompt, options_vec).prompt()?;
-    if selected == NEW_PERSON {
-        Text::new(prompt).prompt()
-    } else {
-        Ok(selected.to_string())
+}
+
+impl Display for RecordId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
+impl FromStr for RecordId {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse()
+            .map(Self)
+    }
+}
+
+/// turns user input like `#vacation-2026 work` into a set of tags without the leading `#`
+pub fn parse_tags(input: &str) -> BTreeSet<String> {
+    input
+        .split([' ', ','])
+        .map(|tag| tag.trim().trim_start_matches('#'))
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ExpenseInfo {
     pub category: ExpenseCategory,
     pub description: Option<String>,
     pub date: NaiveDate,
     creation_date: NaiveDate,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    #[serde(default)]
+    pub id: RecordId,
 }
 
 impl ExpenseInfo {
-    pub fn new() -> Result<Self, KakeboError> {
-        let creation_date = Local::now().date_naive();
-        let date = DateSelect::new("Date:")
-            .with_week_start(Weekday::Mon)
-            .prompt()?;
-        let category_text = Select::new("Category:", ExpenseCategory::options()).prompt()?;
-        let category_text = if category_text == "Other" {
-            Text::new("Other category:")
-                .with_validator(required!("Require non-empty category"))
-                .prompt()?
-        } else {
-            category_text.to_string()
-        };
-        let category = ExpenseCategory::from(category_text);
-        let description = Text::new("Description:").prompt()?;
-        let description = (!description.is_empty()).then_some(description);
-        Ok(Self {
-            creation_date,
-            date,
-            description,
-            category,
-        })
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(tag.trim_start_matches('#'))
+    }
+
+    /// a short name of the expense without ID, date or tags
+    pub fn title(&self) -> String {
+        self.description
+            .clone()
+            .unwrap_or_else(|| self.category.to_string())
+    }
+}
+
+impl Audited for ExpenseInfo {
+    fn audit_fields(&self, fields: &mut AuditFields) {
+        fields.add("date", self.date);
+        fields.add("category", &self.category);
+        if let Some(description) = &self.description {
+            fields.add("description", description);
+        }
+        if !self.tags.is_empty() {
+            let tags: Vec<_> = self.tags.iter().map(|tag| format!("#{tag}")).collect();
+            fields.add("tags", tags.join(" "));
+        }
     }
 }
 
 impl Display for ExpenseInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.id.is_assigned() {
+            write!(f, "[{}] ", self.id)?;
+        }
         write!(
             f,
             "{} {}: {}",
@@ -107,68 +116,41 @@ impl Display for ExpenseInfo {
             self.description
                 .as_ref()
                 .map_or("No description", |descr| descr.as_str())
-        )
+        )?;
+        for tag in &self.tags {
+            write!(f, " #{}", tag)?;
+        }
+        Ok(())
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub enum ExpenseCategory {
-    ReplacementOrRepair,
-    Groceries,
-    Canteen,
-    Family,
-    Friends,
-    Hobby,
-    Restaurant,
-    Entertainment,
-    Other(String),
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-impl From<String> for ExpenseCategory {
-    fn from(value: String) -> Self {
-        match value.as_str() {
-            "Replacement or Repair" => Self::ReplacementOrRepair,
-            "Groceries" => Self::Groceries,
-            "Canteen" => Self::Canteen,
-            "Family" => Self::Family,
-            "Friends" => Self::Friends,
-            "Hobby" => Self::Hobby,
-            "Restaurant" => Self::Restaurant,
-            "Entertainment" => Self::Entertainment,
-            _ => Self::Other(value),
-        }
+    fn tags(tags: &[&str]) -> BTreeSet<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
     }
-}
 
-impl Display for ExpenseCategory {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let str = match self {
-            ExpenseCategory::ReplacementOrRepair => "Replacement or Repair",
-            ExpenseCategory::Groceries => "Groceries",
-            ExpenseCategory::Canteen => "Canteen",
-            ExpenseCategory::Family => "Family",
-            ExpenseCategory::Friends => "Friends",
-            ExpenseCategory::Hobby => "Hobby",
-            ExpenseCategory::Restaurant => "Restaurant",
-            ExpenseCategory::Entertainment => "Entertainment",
-            ExpenseCategory::Other(ref inner) => inner.as_str(),
-        };
-        write!(f, "{}", str)
+    #[test]
+    fn tags_are_separated_by_spaces_and_commas() {
+        assert_eq!(
+            parse_tags("#vacation-2026 work,#food, trip"),
+            tags(&["food", "trip", "vacation-2026", "work"])
+        );
+    }
+
+    #[test]
+    fn duplicate_and_empty_tags_are_dropped() {
+        assert_eq!(parse_tags("#work work ,, # #work"), tags(&["work"]));
+        assert!(parse_tags("  ").is_empty());
     }
-}
 
-impl ExpenseCategory {
-    fn options() -> Vec<&'static str> {
-        vec![
-            "Replacement or Repair",
-            "Groceries",
-            "Canteen",
-            "Family",
-            "Friends",
-            "Hobby",
-            "Restaurant",
-            "Entertainment",
-            "Other",
-        ]
+    #[test]
+    fn has_tag_ignores_the_hash() {
+        let info = crate::testing::info("2026-03-01", "Food", "Lunch #work");
+        assert!(info.has_tag("work"));
+        assert!(info.has_tag("#work"));
+        assert!(!info.has_tag("wor"));
     }
 }
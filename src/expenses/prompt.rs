@@ -0,0 +1,145 @@
+use std::{collections::BTreeSet, iter::once};
+
+use chrono::Local;
+use rust_decimal::Decimal;
+
+use crate::{
+    errors::KakeboError,
+    prompter::{ask, choose, Prompter, Question},
+    Environment, KakeboConfig,
+};
+
+use super::{category::ExpenseCategory, parse_tags, ExpenseInfo, RecordId};
+
+/// parses a positive amount, which can be written as a sum like `12.50 + 3`
+pub fn parse_amount(input: &str) -> Result<Decimal, String> {
+    let mut sum = Decimal::ZERO;
+    for part in input.split('+') {
+        let parsed = part
+            .trim()
+            .parse::<Decimal>()
+            .ok()
+            .and_then(|part| sum.checked_add(part));
+        sum = parsed.ok_or("Please type a valid number or sum of valid numbers")?;
+    }
+    if sum > Decimal::ZERO {
+        Ok(sum)
+    } else {
+        Err("Amount must be positive (non-zero).".to_string())
+    }
+}
+
+/// parses a positive amount of at most `limit`, `what` says what the limit is
+pub fn parse_limited_amount(input: &str, limit: Decimal, what: &str) -> Result<Decimal, String> {
+    let amount = input
+        .trim()
+        .parse::<Decimal>()
+        .map_err(|_| "Please type a valid number")?;
+    if amount <= Decimal::ZERO {
+        Err("Amount must be positive (non-zero).".to_string())
+    } else if amount > limit {
+        Err(format!("Only {:.2} {}.", limit, what))
+    } else {
+        Ok(amount)
+    }
+}
+
+pub fn money_amount(
+    prompter: &mut dyn Prompter,
+    config: &KakeboConfig,
+    name: &str,
+) -> Result<Decimal, KakeboError> {
+    let message = format!("Amount {name}:");
+    let help = format!(
+        "Type the amount in {} using a decimal point as a separator",
+        config.currency
+    );
+    ask(
+        prompter,
+        Question::new(&message).with_help(&help),
+        parse_amount,
+    )
+}
+
+pub const NEW_PERSON: &str = "Add new Person";
+const NEW_CATEGORY: &str = "Add new Category";
+
+pub fn person(
+    prompter: &mut dyn Prompter,
+    prompt: &str,
+    people: &BTreeSet<String>,
+) -> Result<String, KakeboError> {
+    if people.is_empty() {
+        return prompter.input(&Question::new(prompt));
+    }
+    let options: Vec<_> = once(NEW_PERSON)
+        .chain(people.iter().map(String::as_str))
+        .collect();
+    let selected = choose(prompter, prompt, options)?;
+    if selected == NEW_PERSON {
+        prompter.input(&Question::new(prompt))
+    } else {
+        Ok(selected.to_string())
+    }
+}
+
+pub fn category(
+    prompter: &mut dyn Prompter,
+    categories: &[String],
+) -> Result<ExpenseCategory, KakeboError> {
+    let options: Vec<_> = categories
+        .iter()
+        .map(String::as_str)
+        .chain(once(NEW_CATEGORY))
+        .collect();
+    let selected = choose(prompter, "Category:", options)?;
+    let name = if selected == NEW_CATEGORY {
+        let question = Question::new("New category:").with_validator(|input| {
+            super::category::validate(input)
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        });
+        prompter.input(&question)?
+    } else {
+        selected.to_string()
+    };
+    Ok(ExpenseCategory::from(name))
+}
+
+pub fn tags(
+    prompter: &mut dyn Prompter,
+    known_tags: &BTreeSet<String>,
+) -> Result<BTreeSet<String>, KakeboError> {
+    let question = Question::new("Tags:")
+        .with_help("Space separated, e.g. #vacation-2026 #work (optional)")
+        .with_suggestions(known_tags.iter().map(|tag| format!("#{tag}")).collect());
+    let input = prompter.input(&question)?;
+    Ok(parse_tags(&input))
+}
+
+/// asks for a description, an empty one means there is none
+pub fn description(prompter: &mut dyn Prompter) -> Result<Option<String>, KakeboError> {
+    let description = prompter.input(&Question::new("Description:"))?;
+    Ok((!description.is_empty()).then_some(description))
+}
+
+impl ExpenseInfo {
+    pub fn new(
+        prompter: &mut dyn Prompter,
+        environment: &Environment,
+    ) -> Result<Self, KakeboError> {
+        let creation_date = Local::now().date_naive();
+        let date = prompter.date("Date:")?;
+        let category = category(prompter, &environment.categories)?;
+        let description = description(prompter)?;
+        let tags = tags(prompter, &environment.tags)?;
+        Ok(Self {
+            creation_date,
+            date,
+            description,
+            category,
+            tags,
+            id: RecordId::default(),
+        })
+    }
+}
@@ -1,23 +1,84 @@
 use std::fmt::Display;
 
+use chrono::Datelike;
+use chrono::Days;
+use chrono::Months;
 use chrono::NaiveDate;
-use chrono::Weekday;
-use chronoutil::RelativeDuration;
-use inquire::validator::Validation;
-use inquire::Confirm;
-use inquire::CustomType;
-use inquire::DateSelect;
-use inquire::Select;
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::audit::{AuditFields, Audited};
 use crate::errors::KakeboError;
+use crate::prompter::{ask, choose, Prompter, Question};
 use crate::DisplayableExpense;
+use crate::Environment;
 use crate::KakeboConfig;
 
+use super::group_expense::GroupExpense;
 use super::money_amount;
+use super::rrule::RecurrenceRule;
 use super::ExpenseInfo;
+use super::RecordId;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OccurrenceStatus {
+    Due,
+    Paid,
+    Skipped,
+}
+
+impl Display for OccurrenceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OccurrenceStatus::Due => write!(f, "due"),
+            OccurrenceStatus::Paid => write!(f, "paid"),
+            OccurrenceStatus::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+/// a single dated instance of a recurring expense, its amount can differ from the usual one
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Occurrence {
+    pub date: NaiveDate,
+    pub amount: Decimal,
+    pub status: OccurrenceStatus,
+    /// the group expense created for this occurrence of a shared recurring expense
+    #[serde(default)]
+    pub group_id: Option<RecordId>,
+}
+
+impl Display for Occurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:8.2} {}", self.date, self.amount, self.status)
+    }
+}
+
+impl Occurrence {
+    pub fn counts(&self) -> bool {
+        self.status != OccurrenceStatus::Skipped
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OccurrenceAction {
+    MarkPaid,
+    MarkDue,
+    Skip,
+    Adjust,
+}
+
+impl Display for OccurrenceAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OccurrenceAction::MarkPaid => write!(f, "Mark as paid"),
+            OccurrenceAction::MarkDue => write!(f, "Mark as due"),
+            OccurrenceAction::Skip => write!(f, "Skip"),
+            OccurrenceAction::Adjust => write!(f, "Adjust the amount"),
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RecurringExpense {
@@ -25,6 +86,15 @@ pub struct RecurringExpense {
     pub info: ExpenseInfo,
     every: DateDelta,
     end_date: Option<NaiveDate>,
+    /// the occurrences recorded so far, ordered by date
+    #[serde(default)]
+    pub occurrences: Vec<Occurrence>,
+    /// how the expense is split if it is shared, `amount` is then the total of the bill
+    #[serde(default)]
+    pub group: Option<GroupExpense>,
+    /// when occurrences were first recorded, the ones scheduled before were paid without a record
+    #[serde(default)]
+    recorded_from: Option<NaiveDate>,
 }
 
 impl Display for RecurringExpense {
@@ -36,9 +106,37 @@ impl Display for RecurringExpense {
         };
         write!(
             f,
-            "{} ({:8.2} every {} {})",
+            "{} ({:8.2} {} {}",
             self.info, self.amount, self.every, end
-        )
+        )?;
+        if let Some(group) = &self.group {
+            write!(f, ", shared with {}", group.people.join(", "))?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Audited for RecurringExpense {
+    fn audit_fields(&self, fields: &mut AuditFields) {
+        self.info.audit_fields(fields);
+        fields.add("amount", format!("{:.2}", self.amount));
+        fields.add("repeats", &self.every);
+        if let Some(end_date) = self.end_date {
+            fields.add("until", end_date);
+        }
+        if let Some(group) = &self.group {
+            group.audit_split(fields);
+        }
+        if let Some(recorded_from) = self.recorded_from {
+            fields.add("recorded from", recorded_from);
+        }
+        for occurrence in &self.occurrences {
+            let mut value = format!("{:.2} {}", occurrence.amount, occurrence.status);
+            if let Some(id) = occurrence.group_id {
+                value.push_str(&format!(" as [{}]", id));
+            }
+            fields.add(format!("occurrence {}", occurrence.date), value);
+        }
     }
 }
 
@@ -50,52 +148,134 @@ impl DisplayableExpense for RecurringExpense {
     fn plural_name() -> &'static str {
         "recurring expenses"
     }
+
+    fn id(&self) -> RecordId {
+        self.info.id
+    }
+
+    fn set_id(&mut self, id: RecordId) {
+        self.info.id = id;
+    }
+
+    fn configured_display(&self, _config: &KakeboConfig) -> String {
+        let mut details = self.to_string();
+        for occurrence in &self.occurrences {
+            details.push_str(&format!("\n  {}", occurrence));
+        }
+        details
+    }
 }
 
 impl RecurringExpense {
-    pub fn amount_in_interval(&self, start: NaiveDate, end: NaiveDate) -> Decimal {
+    /// the scheduled dates from `start` until `end`, both inclusive
+    fn scheduled_dates(&self, start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+        let mut dates = Vec::new();
         if self.info.date > end {
-            return Decimal::ZERO;
-        }
-        if let Some(end_date) = self.end_date {
-            if end_date < start {
-                return Decimal::ZERO;
-            }
+            return dates;
         }
+        let end = self.end_date.map_or(end, |end_date| end_date.min(end));
 
-        let cycle = RelativeDuration::from(&self.every);
-
-        let mut date = self.info.date;
-        while date < start {
-            date = date + cycle;
+        if let DateDelta::Rule(rule) = &self.every {
+            return rule.dates(self.info.date, start, end);
         }
 
-        if let Some(end_date) = self.end_date {
-            if end_date < date {
-                return Decimal::ZERO;
+        let mut index = self.every.first_index_from(self.info.date, start);
+        // the schedule ends where the dates run out of range
+        while let Some(date) = index.and_then(|index| self.every.nth_date(self.info.date, index)) {
+            if date > end {
+                break;
             }
+            dates.push(date);
+            index = index.and_then(|index| index.checked_add(1));
         }
+        dates
+    }
 
-        let mut total_amount = Decimal::ZERO;
-        while date <= end {
-            if let Some(end_date) = self.end_date {
-                if end_date < date {
-                    return total_amount;
-                }
+    /// the occurrences in the interval: the ones scheduled before occurrences were recorded as
+    /// paid, then the recorded ones, then the ones scheduled after the last recorded occurrence
+    pub fn occurrences_in_interval(&self, start: NaiveDate, end: NaiveDate) -> Vec<Occurrence> {
+        let last_recorded = self.occurrences.last().map(|occurrence| occurrence.date);
+        let scheduled = |status| {
+            move |date| Occurrence {
+                date,
+                amount: self.amount,
+                status,
+                group_id: None,
             }
-            total_amount += self.amount;
-            date = date + cycle;
+        };
+        let (before, mut after): (Vec<_>, Vec<_>) = self
+            .scheduled_dates(start, end)
+            .into_iter()
+            .partition(|&date| self.recorded_from.is_some_and(|from| date < from));
+        after.retain(|&date| last_recorded.is_none_or(|last| date > last));
+        let recorded = self
+            .occurrences
+            .iter()
+            .filter(|occurrence| occurrence.date >= start && occurrence.date <= end)
+            .cloned();
+        before
+            .into_iter()
+            .map(scheduled(OccurrenceStatus::Paid))
+            .chain(recorded)
+            .chain(after.into_iter().map(scheduled(OccurrenceStatus::Due)))
+            .collect()
+    }
+
+    /// what the user pays in the interval, occurrences that became group expenses are left out
+    pub fn amount_in_interval(&self, start: NaiveDate, end: NaiveDate) -> Decimal {
+        self.occurrences_in_interval(start, end)
+            .iter()
+            .filter(|occurrence| occurrence.counts() && occurrence.group_id.is_none())
+            .map(|occurrence| self.user_amount(occurrence))
+            .sum()
+    }
+
+    /// the user's part of an occurrence, only their share if the expense is shared
+    pub fn user_amount(&self, occurrence: &Occurrence) -> Decimal {
+        match &self.group {
+            Some(group) => group.user_share_of(occurrence.amount),
+            None => occurrence.amount,
         }
-        total_amount
+    }
+
+    pub fn end_date(&self) -> Option<NaiveDate> {
+        self.end_date
+    }
+
+    /// the group expense for an occurrence of a shared recurring expense
+    pub fn group_expense(&self, occurrence: &Occurrence, id: RecordId) -> Option<GroupExpense> {
+        let group = self.group.as_ref()?;
+        let mut info = self.info.clone();
+        info.date = occurrence.date;
+        info.id = id;
+        Some(group.instantiate(info, occurrence.amount))
+    }
+
+    /// records every occurrence scheduled up to `until` that is not recorded yet, the first time
+    /// only from `until` on
+    pub fn materialize(&mut self, until: NaiveDate) {
+        let recorded_from = *self.recorded_from.get_or_insert(until);
+        let start = self
+            .occurrences
+            .last()
+            .map_or(self.info.date.max(recorded_from), |occurrence| {
+                occurrence.date + Days::new(1)
+            });
+        let new_occurrences = self.occurrences_in_interval(start, until);
+        self.occurrences.extend(new_occurrences);
     }
 }
 
+/// the largest number of days, weeks, months or years between two occurrences
+const MAX_INTERVAL: u32 = 1000;
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 enum DateDelta {
-    Days(u8),
-    Weeks(u8),
-    Months(u8),
-    Years(u8),
+    Days(u32),
+    Weeks(u32),
+    Months(u32),
+    Years(u32),
+    Rule(RecurrenceRule),
 }
 
 impl Display for DateDelta {
@@ -109,58 +289,121 @@ impl Display for DateDelta {
             DateDelta::Months(n) => write!(f, "every {} months", n),
             DateDelta::Years(1) => write!(f, "every year"),
             DateDelta::Years(n) => write!(f, "every {} years", n),
+            DateDelta::Rule(rule) => write!(f, "{}", rule),
         }
     }
 }
 
-impl From<&DateDelta> for RelativeDuration {
-    fn from(value: &DateDelta) -> Self {
-        match *value {
-            DateDelta::Days(days) => RelativeDuration::days(days as i64),
-            DateDelta::Weeks(weeks) => RelativeDuration::weeks(weeks as i64),
-            DateDelta::Months(months) => RelativeDuration::months(months as i32),
-            DateDelta::Years(years) => RelativeDuration::years(years as i32),
+impl DateDelta {
+    /// the date `index` steps after `first`, days past the end of a month are clamped to its
+    /// last day without shifting the later dates, `None` if it is out of range
+    fn nth_date(&self, first: NaiveDate, index: u32) -> Option<NaiveDate> {
+        let index = u64::from(index);
+        match *self {
+            DateDelta::Days(days) => first.checked_add_days(Days::new(u64::from(days) * index)),
+            DateDelta::Weeks(weeks) => {
+                first.checked_add_days(Days::new(u64::from(weeks) * 7 * index))
+            }
+            DateDelta::Months(months) => first
+                .checked_add_months(Months::new(u32::try_from(u64::from(months) * index).ok()?)),
+            DateDelta::Years(years) => first.checked_add_months(Months::new(
+                u32::try_from(u64::from(years) * 12 * index).ok()?,
+            )),
+            DateDelta::Rule(_) => unreachable!("Rules are not a fixed step"),
+        }
+    }
+
+    /// the index of the first date on or after `date`, computed without stepping through the
+    /// earlier ones, `None` if it is out of range
+    fn first_index_from(&self, first: NaiveDate, date: NaiveDate) -> Option<u32> {
+        if date <= first {
+            return Some(0);
+        }
+        let days = (date - first).num_days() as u64;
+        let months =
+            ((date.year() - first.year()) * 12 + date.month() as i32 - first.month() as i32) as u64;
+        let index = match *self {
+            DateDelta::Days(days_step) => {
+                return u32::try_from(days.div_ceil(u64::from(days_step))).ok();
+            }
+            DateDelta::Weeks(weeks) => {
+                return u32::try_from(days.div_ceil(u64::from(weeks) * 7)).ok();
+            }
+            DateDelta::Months(step) => months / u64::from(step),
+            DateDelta::Years(step) => months / (u64::from(step) * 12),
+            DateDelta::Rule(_) => unreachable!("Rules are not a fixed step"),
+        };
+        let index = u32::try_from(index).ok()?;
+        // the date of `index` lies in the month of `date` or before, clamping can only make it
+        // earlier, so the next one is always after `date`
+        if self.nth_date(first, index)? < date {
+            index.checked_add(1)
+        } else {
+            Some(index)
         }
     }
 }
 
 impl RecurringExpense {
-    pub fn new(config: &KakeboConfig) -> Result<Self, KakeboError> {
-        let cycle_units: Vec<&str> = vec!["Day(s)", "Week(s)", "Month(s)", "Year(s)"];
-
-        let info = ExpenseInfo::new()?;
-        let amount = money_amount(config, &config.user_name)?;
-
-        let cycle_unit = Select::new("How often does this repeat? (unit)", cycle_units).prompt()?;
-        let cycle_amount = CustomType::<u8>::new("How often does this repeat? (amount)")
-            .with_validator(|&input: &u8| {
-                if input == 0 {
-                    Ok(Validation::Invalid(
-                        "Repeating interval must be positive (non-zero).".into(),
-                    ))
-                } else {
-                    Ok(Validation::Valid)
+    pub fn new(
+        prompter: &mut dyn Prompter,
+        environment: &Environment,
+        config: &KakeboConfig,
+    ) -> Result<Self, KakeboError> {
+        let cycle_units: Vec<&str> = vec![
+            "Day(s)",
+            "Week(s)",
+            "Month(s)",
+            "Year(s)",
+            "Custom rule (RRULE)",
+        ];
+
+        let info = ExpenseInfo::new(prompter, environment)?;
+        let shared = prompter.confirm("Is this expense shared with others?")?;
+        let (amount, group) = if shared {
+            let group = GroupExpense::split(prompter, info.clone(), environment, config)?;
+            (group.total_amount(), Some(group))
+        } else {
+            (money_amount(prompter, config, &config.user_name)?, None)
+        };
+
+        let cycle_unit = choose(prompter, "How often does this repeat? (unit)", cycle_units)?;
+        let every = if cycle_unit == "Custom rule (RRULE)" {
+            let question = Question::new("Recurrence rule:").with_help(
+                "e.g. FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1 or FREQ=MONTHLY;BYMONTHDAY=1,15",
+            );
+            let rule = ask(prompter, question, |input| {
+                input
+                    .parse::<RecurrenceRule>()
+                    .map_err(|err| err.to_string())
+            })?;
+            DateDelta::Rule(rule)
+        } else {
+            let question = Question::new("How often does this repeat? (amount)");
+            let cycle_amount = ask(prompter, question, |input| {
+                match input.trim().parse::<u32>() {
+                    Ok(0) => Err("Repeating interval must be positive (non-zero).".to_string()),
+                    Ok(amount) if amount > MAX_INTERVAL => Err(format!(
+                        "Repeating interval must be at most {}.",
+                        MAX_INTERVAL
+                    )),
+                    Ok(amount) => Ok(amount),
+                    Err(_) => Err("Please type a valid positive number".to_string()),
                 }
-            })
-            .with_error_message("Please type a valid positive number")
-            .with_formatter(&|amount| format!("Every {} {}", amount, cycle_unit))
-            .prompt()?;
-
-        let every = match cycle_unit {
-            "Day(s)" => DateDelta::Days(cycle_amount),
-            "Week(s)" => DateDelta::Weeks(cycle_amount),
-            "Month(s)" => DateDelta::Months(cycle_amount),
-            "Year(s)" => DateDelta::Years(cycle_amount),
-            _ => unreachable!(),
+            })?;
+
+            match cycle_unit {
+                "Day(s)" => DateDelta::Days(cycle_amount),
+                "Week(s)" => DateDelta::Weeks(cycle_amount),
+                "Month(s)" => DateDelta::Months(cycle_amount),
+                "Year(s)" => DateDelta::Years(cycle_amount),
+                _ => unreachable!(),
+            }
         };
 
-        let has_end = Confirm::new("Does this recurring expense have an end date?").prompt()?;
+        let has_end = prompter.confirm("Does this recurring expense have an end date?")?;
         let end_date = if has_end {
-            Some(
-                DateSelect::new("Date:")
-                    .with_week_start(Weekday::Mon)
-                    .prompt()?,
-            )
+            Some(prompter.date("Date:")?)
         } else {
             None
         };
@@ -170,13 +413,262 @@ impl RecurringExpense {
             amount,
             every,
             end_date,
+            occurrences: Vec::new(),
+            group,
+            recorded_from: None,
         };
-        new_instance.configured_display(config);
+        prompter.message(&new_instance.configured_display(config));
 
-        if Confirm::new("Save this expense?").prompt()? {
+        if prompter.confirm("Save this expense?")? {
             Ok(new_instance)
         } else {
             Err(KakeboError::ExpenseCreationAborted)
         }
     }
+
+    pub fn edit(
+        &mut self,
+        prompter: &mut dyn Prompter,
+        config: &KakeboConfig,
+    ) -> Result<bool, KakeboError> {
+        prompter.message(&self.configured_display(config));
+        if self.occurrences.is_empty() {
+            prompter.message("No occurrences recorded yet.");
+            return Ok(false);
+        }
+        let options: Vec<_> = self.occurrences.iter().cloned().rev().collect();
+        let selected = match choose(prompter, "Which occurrence do you want to edit?", options) {
+            Err(KakeboError::PromptCanceled) => return Ok(false),
+            selected => selected?,
+        };
+        let occurrence = self
+            .occurrences
+            .iter_mut()
+            .find(|occurrence| occurrence.date == selected.date)
+            .expect("The occurrence was selected from this expense");
+        if let Some(id) = occurrence.group_id {
+            prompter.message(&format!(
+                "This occurrence is the group expense [{}], edit that one instead.",
+                id
+            ));
+            return Ok(false);
+        }
+
+        let actions = vec![
+            OccurrenceAction::MarkPaid,
+            OccurrenceAction::MarkDue,
+            OccurrenceAction::Skip,
+            OccurrenceAction::Adjust,
+        ];
+        match choose(prompter, "What do you want to do?", actions)? {
+            OccurrenceAction::MarkPaid => occurrence.status = OccurrenceStatus::Paid,
+            OccurrenceAction::MarkDue => occurrence.status = OccurrenceStatus::Due,
+            OccurrenceAction::Skip => occurrence.status = OccurrenceStatus::Skipped,
+            OccurrenceAction::Adjust => {
+                occurrence.amount = money_amount(prompter, config, &occurrence.date.to_string())?;
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prompter::ScriptedPrompter;
+    use crate::testing::{amount, date, environment, recurring};
+
+    use super::*;
+
+    fn dates(expense: &RecurringExpense, start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+        expense
+            .occurrences_in_interval(start, end)
+            .iter()
+            .map(|occurrence| occurrence.date)
+            .collect()
+    }
+
+    fn statuses(
+        expense: &RecurringExpense,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<(NaiveDate, OccurrenceStatus)> {
+        expense
+            .occurrences_in_interval(start, end)
+            .iter()
+            .map(|occurrence| (occurrence.date, occurrence.status))
+            .collect()
+    }
+
+    #[test]
+    fn occurrences_before_the_first_recording_count_as_paid() {
+        let mut expense = recurring("2026-01-01", "Month(s)", "1");
+        expense.materialize(date(2026, 3, 15));
+        assert!(expense.occurrences.is_empty());
+        assert_eq!(
+            statuses(&expense, date(2026, 1, 1), date(2026, 4, 30)),
+            [
+                (date(2026, 1, 1), OccurrenceStatus::Paid),
+                (date(2026, 2, 1), OccurrenceStatus::Paid),
+                (date(2026, 3, 1), OccurrenceStatus::Paid),
+                (date(2026, 4, 1), OccurrenceStatus::Due),
+            ]
+        );
+
+        expense.materialize(date(2026, 5, 2));
+        let recorded: Vec<_> = expense
+            .occurrences
+            .iter()
+            .map(|occurrence| (occurrence.date, occurrence.status))
+            .collect();
+        assert_eq!(
+            recorded,
+            [
+                (date(2026, 4, 1), OccurrenceStatus::Due),
+                (date(2026, 5, 1), OccurrenceStatus::Due)
+            ]
+        );
+        assert_eq!(
+            expense.amount_in_interval(date(2026, 1, 1), date(2026, 6, 30)),
+            amount("300")
+        );
+    }
+
+    #[test]
+    fn an_expense_starting_later_is_recorded_from_its_start() {
+        let mut expense = recurring("2026-03-10", "Week(s)", "1");
+        expense.materialize(date(2026, 3, 1));
+        expense.materialize(date(2026, 3, 20));
+        let dates: Vec<_> = expense
+            .occurrences
+            .iter()
+            .map(|occurrence| occurrence.date)
+            .collect();
+        assert_eq!(dates, [date(2026, 3, 10), date(2026, 3, 17)]);
+    }
+
+    #[test]
+    fn rule_for_the_last_business_day() {
+        let expense = recurring(
+            "2026-01-01",
+            "Custom rule (RRULE)",
+            "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1",
+        );
+        assert_eq!(
+            dates(&expense, date(2026, 1, 1), date(2026, 3, 31)),
+            [date(2026, 1, 30), date(2026, 2, 27), date(2026, 3, 31)]
+        );
+    }
+
+    #[test]
+    fn rule_for_the_first_and_fifteenth() {
+        let expense = recurring(
+            "2026-01-01",
+            "Custom rule (RRULE)",
+            "FREQ=MONTHLY;BYMONTHDAY=1,15",
+        );
+        assert_eq!(
+            dates(&expense, date(2026, 1, 1), date(2026, 2, 20)),
+            [
+                date(2026, 1, 1),
+                date(2026, 1, 15),
+                date(2026, 2, 1),
+                date(2026, 2, 15)
+            ]
+        );
+    }
+
+    #[test]
+    fn rule_for_the_second_tuesday() {
+        let expense = recurring(
+            "2026-01-01",
+            "Custom rule (RRULE)",
+            "FREQ=MONTHLY;BYDAY=2TU",
+        );
+        assert_eq!(
+            dates(&expense, date(2026, 1, 1), date(2026, 3, 31)),
+            [date(2026, 1, 13), date(2026, 2, 10), date(2026, 3, 10)]
+        );
+    }
+
+    #[test]
+    fn the_interval_is_bounded() {
+        let config = KakeboConfig::default();
+        for every in ["0", "1001", "300000"] {
+            let mut prompter =
+                ScriptedPrompter::new(["2026-01-01", "Rent", "", "", "n", "50", "Year(s)", every]);
+            assert!(matches!(
+                RecurringExpense::new(&mut prompter, &environment("Rent"), &config),
+                Err(KakeboError::InvalidArgument(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn the_schedule_ends_where_the_dates_run_out() {
+        let end = NaiveDate::MAX;
+        for every in [
+            DateDelta::Days(u32::MAX),
+            DateDelta::Weeks(u32::MAX),
+            DateDelta::Months(u32::MAX),
+            DateDelta::Years(300_000),
+        ] {
+            let mut expense = recurring("2026-01-01", "Day(s)", "1");
+            expense.every = every;
+            assert_eq!(dates(&expense, date(2026, 1, 1), end), [date(2026, 1, 1)]);
+            assert!(dates(&expense, date(2026, 1, 2), end).is_empty());
+        }
+
+        let mut expense = recurring("2026-01-01", "Year(s)", "1000");
+        expense.materialize(date(2026, 1, 1));
+        expense.materialize(end);
+        assert_eq!(expense.occurrences.len(), 261);
+        assert_eq!(expense.occurrences[260].date, date(262_026, 1, 1));
+    }
+
+    #[test]
+    fn monthly_dates_are_clamped_to_the_end_of_the_month() {
+        let expense = recurring("2026-01-31", "Month(s)", "1");
+        assert_eq!(
+            dates(&expense, date(2026, 1, 1), date(2026, 4, 30)),
+            [
+                date(2026, 1, 31),
+                date(2026, 2, 28),
+                date(2026, 3, 31),
+                date(2026, 4, 30)
+            ]
+        );
+        // a leap day comes back every four years
+        let expense = recurring("2024-02-29", "Year(s)", "1");
+        assert_eq!(
+            dates(&expense, date(2027, 1, 1), date(2028, 12, 31)),
+            [date(2027, 2, 28), date(2028, 2, 29)]
+        );
+    }
+
+    #[test]
+    fn later_intervals_match_the_whole_schedule() {
+        for (unit, every) in [
+            ("Day(s)", "3"),
+            ("Week(s)", "2"),
+            ("Month(s)", "1"),
+            ("Month(s)", "5"),
+            ("Year(s)", "2"),
+            ("Custom rule (RRULE)", "FREQ=DAILY"),
+        ] {
+            let expense = recurring("2020-01-31", unit, every);
+            let whole = dates(&expense, date(2020, 1, 1), date(2030, 12, 31));
+            for (start, end) in [
+                (date(2026, 3, 1), date(2026, 3, 3)),
+                (date(2026, 2, 28), date(2026, 7, 31)),
+                (date(2029, 1, 31), date(2030, 12, 31)),
+            ] {
+                let expected: Vec<_> = whole
+                    .iter()
+                    .copied()
+                    .filter(|&date| date >= start && date <= end)
+                    .collect();
+                assert_eq!(dates(&expense, start, end), expected, "{} {}", every, unit);
+            }
+        }
+    }
 }
@@ -0,0 +1,74 @@
+use chrono::{Local, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::audit::{AuditFields, Audited};
+use crate::errors::KakeboError;
+use crate::prompter::{ask, Prompter, Question};
+use crate::KakeboConfig;
+
+use super::prompt::parse_limited_amount;
+
+/// a single instalment paid back on a debt or an advancement
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Repayment {
+    pub date: NaiveDate,
+    pub amount: Decimal,
+}
+
+impl Repayment {
+    pub fn today(amount: Decimal) -> Self {
+        Self {
+            date: Local::now().date_naive(),
+            amount,
+        }
+    }
+
+    /// asks for a repayment of at most the `outstanding` amount
+    pub fn new(prompter: &mut dyn Prompter, outstanding: Decimal) -> Result<Self, KakeboError> {
+        let date = prompter.date("Date of the repayment:")?;
+        let amount = ask(
+            prompter,
+            Question::new("Amount paid back:").with_default(outstanding),
+            move |input| parse_limited_amount(input, outstanding, "is outstanding"),
+        )?;
+        Ok(Self { date, amount })
+    }
+}
+
+impl Audited for Repayments {
+    fn audit_fields(&self, fields: &mut AuditFields) {
+        for (i, repayment) in self.0.iter().enumerate() {
+            fields.add(
+                format!("repayment {}", i + 1),
+                format!("{} {:.2}", repayment.date, repayment.amount),
+            );
+        }
+    }
+}
+
+/// the instalments paid back so far, in the order they were recorded
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Repayments(Vec<Repayment>);
+
+impl Repayments {
+    pub fn total(&self) -> Decimal {
+        self.0.iter().map(|repayment| repayment.amount).sum()
+    }
+
+    pub fn push(&mut self, repayment: Repayment) {
+        self.0.push(repayment);
+    }
+
+    /// a line for every repayment, each starting with a line break
+    pub fn details(&self, config: &KakeboConfig) -> String {
+        let mut details = String::new();
+        for repayment in &self.0 {
+            details.push_str(&format!(
+                "\n  {} repaid {:8.2}{}",
+                repayment.date, repayment.amount, config.currency
+            ));
+        }
+        details
+    }
+}
@@ -0,0 +1,377 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::KakeboError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// a weekday of a `BYDAY` list, optionally restricted to the n-th one of the month
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ByDay {
+    /// e.g. `2` for the second or `-1` for the last one
+    ordinal: Option<i32>,
+    weekday: Weekday,
+}
+
+/// the subset of RFC 5545 recurrence rules kakebo understands, e.g.
+/// `FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1` for the last business day of each month
+///
+/// Supported parts are `FREQ`, `INTERVAL`, `BYDAY`, `BYMONTHDAY`, `BYMONTH`, `BYSETPOS`,
+/// `COUNT` and `UNTIL`. Weekday ordinals in yearly rules count within the month.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RecurrenceRule {
+    source: String,
+    frequency: Frequency,
+    interval: u32,
+    by_day: Vec<ByDay>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+    by_set_pos: Vec<i32>,
+    count: Option<usize>,
+    until: Option<NaiveDate>,
+}
+
+fn parse_weekday(input: &str) -> Option<Weekday> {
+    match input {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn parse_list<T: FromStr>(value: &str) -> Option<Vec<T>> {
+    value
+        .split(',')
+        .map(|item| item.trim().parse().ok())
+        .collect()
+}
+
+/// the n-th item of the list starting at 1, counting from the end for negative `n`
+fn nth<T: Copy>(items: &[T], n: i32) -> Option<T> {
+    let index = if n > 0 {
+        n as usize - 1
+    } else {
+        items.len().checked_sub(n.unsigned_abs() as usize)?
+    };
+    items.get(index).copied()
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = KakeboError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            |reason: &str| KakeboError::InvalidArgument(format!("Invalid RRULE {}: {}", s, reason));
+        let source = s.trim().trim_start_matches("RRULE:").to_uppercase();
+
+        let mut frequency = None;
+        let mut rule = Self {
+            source: source.clone(),
+            frequency: Frequency::Daily,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            by_set_pos: Vec::new(),
+            count: None,
+            until: None,
+        };
+        for part in source.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| invalid(&format!("{} is not of the form KEY=VALUE", part)))?;
+            match key {
+                "FREQ" => {
+                    frequency = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(invalid(&format!("unsupported frequency {}", value))),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|&interval| interval > 0)
+                        .ok_or_else(|| invalid("INTERVAL must be a positive number"))?
+                }
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(|day| {
+                            // the weekday is split off by bytes
+                            if !day.is_ascii() {
+                                return None;
+                            }
+                            let (ordinal, weekday) = day.split_at(day.len().saturating_sub(2));
+                            let weekday = parse_weekday(weekday)?;
+                            let ordinal = match ordinal.trim_start_matches('+') {
+                                "" => None,
+                                ordinal => Some(ordinal.parse().ok().filter(|&n: &i32| n != 0)?),
+                            };
+                            Some(ByDay { ordinal, weekday })
+                        })
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| invalid("BYDAY must list days like MO, 2TU or -1FR"))?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = parse_list(value)
+                        .filter(|days: &Vec<i32>| {
+                            days.iter()
+                                .all(|&day| day != 0 && (-31..=31).contains(&day))
+                        })
+                        .ok_or_else(|| invalid("BYMONTHDAY must list days from 1 to 31"))?
+                }
+                "BYMONTH" => {
+                    rule.by_month = parse_list(value)
+                        .filter(|months: &Vec<u32>| months.iter().all(|m| (1..=12).contains(m)))
+                        .ok_or_else(|| invalid("BYMONTH must list months from 1 to 12"))?
+                }
+                "BYSETPOS" => {
+                    rule.by_set_pos = parse_list(value)
+                        .filter(|positions: &Vec<i32>| positions.iter().all(|&p| p != 0))
+                        .ok_or_else(|| invalid("BYSETPOS must list non-zero positions"))?
+                }
+                "COUNT" => {
+                    rule.count = Some(
+                        value
+                            .parse()
+                            .map_err(|_| invalid("COUNT must be a number"))?,
+                    )
+                }
+                "UNTIL" => {
+                    let date = value.get(..8).unwrap_or(value);
+                    rule.until = Some(
+                        NaiveDate::parse_from_str(date, "%Y%m%d")
+                            .map_err(|_| invalid("UNTIL must be a date like 20261231"))?,
+                    )
+                }
+                _ => return Err(invalid(&format!("unsupported part {}", key))),
+            }
+        }
+        rule.frequency = frequency.ok_or_else(|| invalid("FREQ is missing"))?;
+        Ok(rule)
+    }
+}
+
+impl TryFrom<String> for RecurrenceRule {
+    type Error = KakeboError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<RecurrenceRule> for String {
+    fn from(value: RecurrenceRule) -> Self {
+        value.source
+    }
+}
+
+impl Display for RecurrenceRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RRULE:{}", self.source)
+    }
+}
+
+impl RecurrenceRule {
+    /// the first day of the `index`-th period after the one containing `first`
+    fn period_start(&self, first: NaiveDate, index: u32) -> Option<NaiveDate> {
+        let steps = index.checked_mul(self.interval)?;
+        match self.frequency {
+            Frequency::Daily => first.checked_add_days(Days::new(steps.into())),
+            Frequency::Weekly => first
+                .week(Weekday::Mon)
+                .first_day()
+                .checked_add_days(Days::new(u64::from(steps) * 7)),
+            Frequency::Monthly => first.with_day(1)?.checked_add_months(Months::new(steps)),
+            Frequency::Yearly => first
+                .with_day(1)?
+                .with_month(1)?
+                .checked_add_months(Months::new(steps.checked_mul(12)?)),
+        }
+    }
+
+    /// the index of the period containing `date`, the periods before it cannot have dates from
+    /// `date` on
+    fn period_index(&self, first: NaiveDate, date: NaiveDate) -> u32 {
+        if date <= first {
+            return 0;
+        }
+        let periods = match self.frequency {
+            Frequency::Daily => (date - first).num_days(),
+            Frequency::Weekly => (date.week(Weekday::Mon).first_day()
+                - first.week(Weekday::Mon).first_day())
+            .num_weeks(),
+            Frequency::Monthly => {
+                i64::from(date.year() - first.year()) * 12 + i64::from(date.month())
+                    - i64::from(first.month())
+            }
+            Frequency::Yearly => i64::from(date.year() - first.year()),
+        };
+        u32::try_from(periods / i64::from(self.interval)).unwrap_or(u32::MAX)
+    }
+
+    /// the candidate dates of a month before `BYSETPOS` is applied
+    fn month_dates(&self, month_start: NaiveDate, first: NaiveDate) -> Vec<NaiveDate> {
+        let days: Vec<_> = month_start
+            .iter_days()
+            .take_while(|date| date.month() == month_start.month())
+            .collect();
+        let by_month_day: Vec<_> = self
+            .by_month_day
+            .iter()
+            .filter_map(|&day| nth(&days, day))
+            .collect();
+        let by_day: Vec<_> = self
+            .by_day
+            .iter()
+            .flat_map(|by_day| {
+                let matching: Vec<_> = days
+                    .iter()
+                    .copied()
+                    .filter(|date| date.weekday() == by_day.weekday)
+                    .collect();
+                match by_day.ordinal {
+                    Some(n) => nth(&matching, n).into_iter().collect(),
+                    None => matching,
+                }
+            })
+            .collect();
+        match (self.by_month_day.is_empty(), self.by_day.is_empty()) {
+            (true, true) => days
+                .into_iter()
+                .filter(|date| date.day() == first.day())
+                .collect(),
+            (false, true) => by_month_day,
+            (true, false) => by_day,
+            (false, false) => by_month_day
+                .into_iter()
+                .filter(|date| by_day.contains(date))
+                .collect(),
+        }
+    }
+
+    /// the dates of one period, sorted and with `BYSETPOS` applied
+    fn period_dates(&self, period_start: NaiveDate, first: NaiveDate) -> Vec<NaiveDate> {
+        let mut dates: Vec<_> = match self.frequency {
+            Frequency::Daily => {
+                let date = period_start;
+                let weekday_matches = self.by_day.is_empty()
+                    || self
+                        .by_day
+                        .iter()
+                        .any(|by_day| by_day.weekday == date.weekday());
+                let month_day_matches = self.by_month_day.is_empty()
+                    || self.by_month_day.iter().any(|&day| {
+                        let days_in_month = date
+                            .with_day(1)
+                            .and_then(|month_start| month_start.checked_add_months(Months::new(1)))
+                            .and_then(|next_month| next_month.pred_opt())
+                            .map_or(31, |last| last.day() as i32);
+                        let day = if day < 0 {
+                            days_in_month + day + 1
+                        } else {
+                            day
+                        };
+                        date.day() as i32 == day
+                    });
+                if weekday_matches && month_day_matches {
+                    vec![date]
+                } else {
+                    Vec::new()
+                }
+            }
+            Frequency::Weekly => {
+                let weekdays: Vec<_> = if self.by_day.is_empty() {
+                    vec![first.weekday()]
+                } else {
+                    self.by_day.iter().map(|by_day| by_day.weekday).collect()
+                };
+                period_start
+                    .iter_days()
+                    .take(7)
+                    .filter(|date| weekdays.contains(&date.weekday()))
+                    .collect()
+            }
+            Frequency::Monthly => self.month_dates(period_start, first),
+            Frequency::Yearly => {
+                let months = if self.by_month.is_empty() {
+                    vec![first.month()]
+                } else {
+                    self.by_month.clone()
+                };
+                months
+                    .into_iter()
+                    .filter_map(|month| period_start.with_month(month))
+                    .flat_map(|month_start| self.month_dates(month_start, first))
+                    .collect()
+            }
+        };
+        if !self.by_month.is_empty() {
+            dates.retain(|date| self.by_month.contains(&date.month()));
+        }
+        dates.sort();
+        dates.dedup();
+        if !self.by_set_pos.is_empty() {
+            let mut selected: Vec<_> = self
+                .by_set_pos
+                .iter()
+                .filter_map(|&position| nth(&dates, position))
+                .collect();
+            selected.sort();
+            selected.dedup();
+            dates = selected;
+        }
+        dates
+    }
+
+    /// the dates of a rule starting at `first` that lie between `start` and `end`, both inclusive
+    pub fn dates(&self, first: NaiveDate, start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+        let end = self.until.map_or(end, |until| until.min(end));
+        let mut dates = Vec::new();
+        let mut count = 0;
+        // only `COUNT` needs to know how many dates came before `start`
+        let first_index = match self.count {
+            Some(_) => 0,
+            None => self.period_index(first, start),
+        };
+        for index in first_index.. {
+            let Some(period_start) = self.period_start(first, index) else {
+                break;
+            };
+            if period_start > end {
+                break;
+            }
+            for date in self.period_dates(period_start, first) {
+                if date < first {
+                    continue;
+                }
+                if date > end || self.count.is_some_and(|max| count >= max) {
+                    return dates;
+                }
+                count += 1;
+                if date >= start {
+                    dates.push(date);
+                }
+            }
+        }
+        dates
+    }
+}
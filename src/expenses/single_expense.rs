@@ -1,16 +1,20 @@
 use std::fmt::Display;
 
-use inquire::Confirm;
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::audit::{AuditFields, Audited};
 use crate::errors::KakeboError;
+use crate::prompter::{choose, Prompter};
 use crate::DisplayableExpense;
+use crate::Environment;
 use crate::KakeboConfig;
 
-use super::money_amount;
+use super::prompt::description;
 use super::ExpenseInfo;
+use super::RecordId;
+use super::{category, money_amount, tags};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SingleExpense {
@@ -24,6 +28,13 @@ impl Display for SingleExpense {
     }
 }
 
+impl Audited for SingleExpense {
+    fn audit_fields(&self, fields: &mut AuditFields) {
+        self.info.audit_fields(fields);
+        fields.add("amount", format!("{:.2}", self.amount));
+    }
+}
+
 impl DisplayableExpense for SingleExpense {
     fn name() -> &'static str {
         "single expense"
@@ -32,20 +43,138 @@ impl DisplayableExpense for SingleExpense {
     fn plural_name() -> &'static str {
         "single expenses"
     }
+
+    fn id(&self) -> RecordId {
+        self.info.id
+    }
+
+    fn set_id(&mut self, id: RecordId) {
+        self.info.id = id;
+    }
 }
 
 impl SingleExpense {
-    pub fn new(config: &KakeboConfig) -> Result<Self, KakeboError> {
-        let info = ExpenseInfo::new()?;
-        let amount = money_amount(config, &config.user_name)?;
+    pub fn new(
+        prompter: &mut dyn Prompter,
+        environment: &Environment,
+        config: &KakeboConfig,
+    ) -> Result<Self, KakeboError> {
+        let info = ExpenseInfo::new(prompter, environment)?;
+        let amount = money_amount(prompter, config, &config.user_name)?;
 
         let new_instance = Self { info, amount };
-        new_instance.configured_display(config);
+        prompter.message(&new_instance.configured_display(config));
 
-        if Confirm::new("Save this expense?").prompt()? {
+        if prompter.confirm("Save this expense?")? {
             Ok(new_instance)
         } else {
             Err(KakeboError::ExpenseCreationAborted)
         }
     }
+
+    /// changes one detail of the expense, returns whether anything was changed
+    pub fn edit(
+        &mut self,
+        prompter: &mut dyn Prompter,
+        environment: &Environment,
+        config: &KakeboConfig,
+    ) -> Result<bool, KakeboError> {
+        prompter.message(&self.configured_display(config));
+        let fields = vec!["Date", "Category", "Description", "Tags", "Amount"];
+        let field = match choose(prompter, "What do you want to change?", fields) {
+            Err(KakeboError::PromptCanceled) => return Ok(false),
+            field => field?,
+        };
+        match field {
+            "Date" => self.info.date = prompter.date("Date:")?,
+            "Category" => self.info.category = category(prompter, &environment.categories)?,
+            "Description" => self.info.description = description(prompter)?,
+            "Tags" => self.info.tags = tags(prompter, &environment.tags)?,
+            "Amount" => self.amount = money_amount(prompter, config, &config.user_name)?,
+            _ => unreachable!(),
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prompter::ScriptedPrompter;
+    use crate::testing::{amount, date, environment, info};
+
+    use super::*;
+
+    fn expense() -> SingleExpense {
+        SingleExpense {
+            amount: amount("5"),
+            info: info("2026-03-01", "Food", "Lunch #work"),
+        }
+    }
+
+    fn edit(expense: &mut SingleExpense, answers: &[&str]) -> bool {
+        let mut prompter = ScriptedPrompter::new(answers.iter().copied());
+        let changed = expense
+            .edit(
+                &mut prompter,
+                &environment("Food"),
+                &KakeboConfig::default(),
+            )
+            .unwrap();
+        assert_eq!(prompter.remaining().count(), 0);
+        changed
+    }
+
+    #[test]
+    fn new_adds_up_the_amount() {
+        let config = KakeboConfig::default();
+        let mut prompter =
+            ScriptedPrompter::new(["2026-03-01", "Food", "Lunch", "#trip", "12.50 + 3", "y"]);
+        let expense = SingleExpense::new(&mut prompter, &environment("Food"), &config).unwrap();
+        assert_eq!(expense.amount, amount("15.50"));
+        assert_eq!(expense.info.description.as_deref(), Some("Lunch"));
+        assert!(expense.info.has_tag("trip"));
+        assert_eq!(prompter.messages().len(), 1);
+    }
+
+    #[test]
+    fn new_rejects_invalid_amounts_and_can_be_aborted() {
+        let config = KakeboConfig::default();
+        let mut prompter = ScriptedPrompter::new(["2026-03-01", "Food", "", "", "-5"]);
+        assert!(matches!(
+            SingleExpense::new(&mut prompter, &environment("Food"), &config),
+            Err(KakeboError::InvalidArgument(_))
+        ));
+        let mut prompter = ScriptedPrompter::new(["2026-03-01", "Food", "", "", "5", "n"]);
+        assert!(matches!(
+            SingleExpense::new(&mut prompter, &environment("Food"), &config),
+            Err(KakeboError::ExpenseCreationAborted)
+        ));
+    }
+
+    #[test]
+    fn edit_changes_one_field() {
+        let mut expense = expense();
+        assert!(edit(&mut expense, &["Amount", "7"]));
+        assert_eq!(expense.amount, amount("7"));
+        assert!(edit(&mut expense, &["Date", "2026-03-02"]));
+        assert!(edit(
+            &mut expense,
+            &["Category", "Add new Category", "Food>Canteen"]
+        ));
+        assert!(edit(&mut expense, &["Description", ""]));
+        assert!(edit(&mut expense, &["Tags", "#team, lunch"]));
+
+        assert_eq!(expense.info.date, date(2026, 3, 2));
+        assert_eq!(expense.info.category.name(), "Food > Canteen");
+        assert_eq!(expense.info.description, None);
+        assert!(expense.info.has_tag("team") && !expense.info.has_tag("work"));
+        assert_eq!(expense.amount, amount("7"));
+    }
+
+    #[test]
+    fn edit_can_be_canceled() {
+        let mut expense = expense();
+        assert!(!edit(&mut expense, &["<esc>"]));
+        assert_eq!(expense, self::expense());
+    }
 }
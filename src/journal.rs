@@ -0,0 +1,303 @@
+use std::fmt::Display;
+
+use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::KakeboError, Expenses};
+
+/// how many operations are kept for undoing, older ones are dropped
+const MAX_OPERATIONS: usize = 100;
+
+/// a mutating command together with the database state it replaced
+///
+/// The state after an applied operation is the state before the next one or the current state,
+/// so only one snapshot is kept: the state before it while it is applied and the state after it
+/// once it is undone.
+#[derive(Serialize, Deserialize, PartialEq, Eq)]
+pub struct Operation {
+    pub timestamp: NaiveDateTime,
+    pub command: String,
+    snapshot: Vec<u8>,
+}
+
+impl std::fmt::Debug for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Operation")
+            .field("timestamp", &self.timestamp)
+            .field("command", &self.command)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            self.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            self.command
+        )
+    }
+}
+
+/// the operations that can be undone and redone, stored inside the encrypted database
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Journal {
+    operations: Vec<Operation>,
+    /// the number of operations currently applied, the ones after them can be redone
+    applied: usize,
+}
+
+impl Journal {
+    /// adds an operation that changed the state from `before` to the current one
+    pub fn record(&mut self, command: String, before: Vec<u8>) {
+        self.operations.truncate(self.applied);
+        self.operations.push(Operation {
+            timestamp: Local::now().naive_local(),
+            command,
+            snapshot: before,
+        });
+        if self.operations.len() > MAX_OPERATIONS {
+            let excess = self.operations.len() - MAX_OPERATIONS;
+            self.operations.drain(..excess);
+        }
+        self.applied = self.operations.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// whether some operations were undone and can be redone
+    pub fn has_undone(&self) -> bool {
+        self.applied < self.operations.len()
+    }
+
+    /// the last `count` operations, newest first, with their number and whether they are undone
+    pub fn recent(
+        &self,
+        count: usize,
+    ) -> impl Iterator<Item = (usize, &Operation, bool)> + use<'_> {
+        let first = self.operations.len().saturating_sub(count);
+        self.operations
+            .iter()
+            .enumerate()
+            .skip(first)
+            .rev()
+            .map(|(i, operation)| (i + 1, operation, i >= self.applied))
+    }
+}
+
+impl Expenses {
+    /// the serialized database state without the journal and the append-only history
+    pub fn snapshot(&mut self) -> Result<Vec<u8>, KakeboError> {
+        let journal = std::mem::take(&mut self.journal);
+        let history = std::mem::take(&mut self.history);
+        let snapshot = rmp_serde::to_vec(self);
+        self.journal = journal;
+        self.history = history;
+        Ok(snapshot?)
+    }
+
+    /// IDs are never handed out twice, so the history of an ID always belongs to one record
+    fn restore(&mut self, snapshot: &[u8]) -> Result<(), KakeboError> {
+        let restored: Expenses = rmp_serde::from_slice(snapshot)?;
+        let previous = std::mem::replace(self, restored);
+        self.journal = previous.journal;
+        self.history = previous.history;
+        self.last_id = previous.last_id.max(self.last_id);
+        Ok(())
+    }
+
+    /// runs `command`, which returns whether it changed anything, and then adds what it changed
+    /// to the history and the operation to the journal so it can be undone
+    pub fn apply<F>(&mut self, command: &str, f: F) -> Result<bool, KakeboError>
+    where
+        F: FnOnce(&mut Self) -> Result<bool, KakeboError>,
+    {
+        let before = self.snapshot()?;
+        let states_before = self.audit_states();
+        let changed = f(self)?;
+        if changed {
+            let states_after = self.audit_states();
+            self.history
+                .record_changes(command, &states_before, &states_after);
+            self.journal.record(command.to_string(), before);
+        }
+        Ok(changed)
+    }
+
+    /// restores `snapshot` and adds what that changed to the history
+    fn restore_as(&mut self, command: &str, snapshot: &[u8]) -> Result<(), KakeboError> {
+        let states_before = self.audit_states();
+        self.restore(snapshot)?;
+        let states_after = self.audit_states();
+        self.history
+            .record_changes(command, &states_before, &states_after);
+        Ok(())
+    }
+
+    /// restores the state before the last applied operation, which is returned
+    pub fn undo(&mut self) -> Result<Option<&Operation>, KakeboError> {
+        if self.journal.applied == 0 {
+            return Ok(None);
+        }
+        let index = self.journal.applied - 1;
+        let current = self.snapshot()?;
+        let before = self.journal.operations[index].snapshot.clone();
+        self.restore_as("undo", &before)?;
+        self.journal.operations[index].snapshot = current;
+        self.journal.applied = index;
+        Ok(Some(&self.journal.operations[index]))
+    }
+
+    /// applies the first undone operation again and returns it
+    pub fn redo(&mut self) -> Result<Option<&Operation>, KakeboError> {
+        if !self.journal.has_undone() {
+            return Ok(None);
+        }
+        let index = self.journal.applied;
+        let current = self.snapshot()?;
+        let after = self.journal.operations[index].snapshot.clone();
+        self.restore_as("redo", &after)?;
+        self.journal.operations[index].snapshot = current;
+        self.journal.applied = index + 1;
+        Ok(Some(&self.journal.operations[index]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audit::AuditAction;
+    use crate::testing::add_single;
+
+    use super::*;
+
+    /// adds a single expense as a journaled operation called `command`
+    fn add(expenses: &mut Expenses, command: &str) {
+        let changed = expenses
+            .apply(command, |expenses| {
+                add_single(expenses, "2026-03-01", "Food", command, "10");
+                Ok(true)
+            })
+            .unwrap();
+        assert!(changed);
+    }
+
+    fn descriptions(expenses: &Expenses) -> Vec<&str> {
+        expenses
+            .single_expenses
+            .iter()
+            .filter_map(|single| single.info.description.as_deref())
+            .collect()
+    }
+
+    fn commands(expenses: &Expenses) -> Vec<(usize, &str, bool)> {
+        expenses
+            .journal
+            .recent(usize::MAX)
+            .map(|(number, operation, undone)| (number, operation.command.as_str(), undone))
+            .collect()
+    }
+
+    #[test]
+    fn undo_and_redo_step_through_the_operations() {
+        let mut expenses = Expenses::default();
+        add(&mut expenses, "first");
+        add(&mut expenses, "second");
+
+        assert_eq!(expenses.undo().unwrap().unwrap().command, "second");
+        assert_eq!(descriptions(&expenses), ["first"]);
+        assert_eq!(
+            commands(&expenses),
+            [(2, "second", true), (1, "first", false)]
+        );
+        assert_eq!(expenses.undo().unwrap().unwrap().command, "first");
+        assert!(descriptions(&expenses).is_empty());
+        assert!(expenses.undo().unwrap().is_none());
+
+        assert_eq!(expenses.redo().unwrap().unwrap().command, "first");
+        assert_eq!(expenses.redo().unwrap().unwrap().command, "second");
+        assert_eq!(descriptions(&expenses), ["first", "second"]);
+        assert!(expenses.redo().unwrap().is_none());
+        assert!(!expenses.journal.has_undone());
+    }
+
+    #[test]
+    fn a_new_operation_drops_the_undone_ones() {
+        let mut expenses = Expenses::default();
+        add(&mut expenses, "first");
+        add(&mut expenses, "second");
+        expenses.undo().unwrap();
+        add(&mut expenses, "third");
+
+        assert!(expenses.redo().unwrap().is_none());
+        assert_eq!(
+            commands(&expenses),
+            [(2, "third", false), (1, "first", false)]
+        );
+        assert_eq!(descriptions(&expenses), ["first", "third"]);
+        expenses.undo().unwrap();
+        assert_eq!(descriptions(&expenses), ["first"]);
+    }
+
+    #[test]
+    fn only_the_latest_operations_are_kept() {
+        let mut expenses = Expenses::default();
+        for i in 0..MAX_OPERATIONS + 5 {
+            add(&mut expenses, &i.to_string());
+        }
+        let commands = commands(&expenses);
+        assert_eq!(commands.len(), MAX_OPERATIONS);
+        assert_eq!(commands.last(), Some(&(1, "5", false)));
+
+        for _ in 0..MAX_OPERATIONS {
+            assert!(expenses.undo().unwrap().is_some());
+        }
+        assert!(expenses.undo().unwrap().is_none());
+        assert_eq!(descriptions(&expenses), ["0", "1", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn undo_never_hands_out_an_id_twice() {
+        let mut expenses = Expenses::default();
+        add(&mut expenses, "first");
+        let first = expenses.single_expenses[0].info.id;
+        expenses.undo().unwrap();
+        assert_eq!(expenses.last_id, first);
+
+        add(&mut expenses, "second");
+        assert!(expenses.single_expenses[0].info.id > first);
+        expenses.undo().unwrap();
+        expenses.redo().unwrap();
+        assert_eq!(expenses.last_id, expenses.single_expenses[0].info.id);
+    }
+
+    #[test]
+    fn only_commands_that_change_something_are_recorded() {
+        let mut expenses = Expenses::default();
+        assert!(!expenses.apply("status", |_| Ok(false)).unwrap());
+        let failed = expenses.apply("add", |_| Err(KakeboError::PromptCanceled));
+        assert!(matches!(failed, Err(KakeboError::PromptCanceled)));
+        assert!(expenses.journal.is_empty());
+        assert_eq!(expenses.history.entries(None).count(), 0);
+
+        add(&mut expenses, "add single");
+        expenses.undo().unwrap();
+        expenses.redo().unwrap();
+        let history: Vec<_> = expenses
+            .history
+            .entries(None)
+            .map(|entry| (entry.command.as_str(), entry.action))
+            .collect();
+        assert_eq!(
+            history,
+            [
+                ("add single", AuditAction::Added),
+                ("undo", AuditAction::Removed),
+                ("redo", AuditAction::Added)
+            ]
+        );
+        assert_eq!(commands(&expenses), [(1, "add single", false)]);
+    }
+}
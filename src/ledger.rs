@@ -0,0 +1,313 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use crate::{expenses::RecordId, Expenses, ANSI_GREEN, ANSI_RED, ANSI_STOP};
+
+/// a single position between the user and another person
+#[derive(Debug)]
+pub struct LedgerItem {
+    pub description: String,
+    /// a short name of the item for messages to the person
+    pub title: String,
+    pub date: Option<NaiveDate>,
+    /// the record the item belongs to, if any
+    pub id: Option<RecordId>,
+    /// what the person still owes the user because of this item
+    pub they_owe: Decimal,
+    /// what the user still owes the person because of this item
+    pub you_owe: Decimal,
+}
+
+impl LedgerItem {
+    pub fn is_settled(&self) -> bool {
+        self.they_owe.is_zero() && self.you_owe.is_zero()
+    }
+}
+
+impl Display for LedgerItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.description)
+    }
+}
+
+/// everything the user and one person owe each other
+#[derive(Debug)]
+pub struct Ledger {
+    pub person: String,
+    pub items: Vec<LedgerItem>,
+}
+
+impl Ledger {
+    pub fn they_owe(&self) -> Decimal {
+        self.items.iter().map(|item| item.they_owe).sum()
+    }
+
+    pub fn you_owe(&self) -> Decimal {
+        self.items.iter().map(|item| item.you_owe).sum()
+    }
+
+    /// positive if the person owes the user money, negative if the user owes them
+    pub fn balance(&self) -> Decimal {
+        self.they_owe() - self.you_owe()
+    }
+
+    /// the person's totals and balance, coloured red if the balance is large
+    pub fn balance_line(&self) -> String {
+        let balance = self.balance();
+        let balance_color = if Decimal::abs(&balance) >= Decimal::from(5) {
+            ANSI_RED
+        } else {
+            ANSI_GREEN
+        };
+        format!(
+            "  {:10} {:8.2}, {:8.2}, TOTAL: {balance_color}{:+8.2}{ANSI_STOP}",
+            self.person,
+            self.they_owe(),
+            self.you_owe(),
+            balance
+        )
+    }
+}
+
+/// the column titles above balance lines
+pub const BALANCE_HEADER: &str = "             they owe   you owe          balance";
+
+impl Expenses {
+    pub fn ledger(&self, person: &str) -> Ledger {
+        let mut items = Vec::new();
+
+        let parts = self
+            .group_expenses
+            .iter()
+            .flat_map(|group_expense| group_expense.parts())
+            .filter(|part| part.person == person);
+        for part in parts {
+            let paid = part.paid.unwrap_or(Decimal::ZERO);
+            items.push(LedgerItem {
+                description: part.to_string(),
+                title: part.info.title(),
+                date: Some(part.info.date),
+                id: Some(part.info.id),
+                they_owe: (part.to_pay - paid).max(Decimal::ZERO),
+                you_owe: (paid - part.to_pay).max(Decimal::ZERO),
+            });
+        }
+
+        let paid_by_person = self
+            .group_expenses
+            .iter()
+            .filter(|group_expense| group_expense.payer.as_deref() == Some(person));
+        for group_expense in paid_by_person {
+            items.push(LedgerItem {
+                description: format!(
+                    "{} {:8.2} (your share) - {}",
+                    person,
+                    group_expense.true_user_amount(),
+                    group_expense.info
+                ),
+                title: group_expense.info.title(),
+                date: Some(group_expense.info.date),
+                id: Some(group_expense.info.id),
+                they_owe: Decimal::ZERO,
+                you_owe: group_expense.user_outstanding(),
+            });
+        }
+
+        for debt in self.debts_owed.iter().filter(|debt| debt.person == person) {
+            items.push(LedgerItem {
+                description: debt.to_string(),
+                title: debt.expense.info.title(),
+                date: Some(debt.expense.info.date),
+                id: Some(debt.expense.info.id),
+                they_owe: Decimal::ZERO,
+                you_owe: debt.outstanding(),
+            });
+        }
+
+        let advancements = self
+            .unpaid_advancements
+            .iter()
+            .filter(|advancement| advancement.person == person);
+        for advancement in advancements {
+            items.push(LedgerItem {
+                description: advancement.to_string(),
+                title: advancement
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| "Advancement".to_string()),
+                date: Some(advancement.date),
+                id: Some(advancement.id),
+                they_owe: advancement.outstanding(),
+                you_owe: Decimal::ZERO,
+            });
+        }
+
+        // a negative overflow is a credit left over from paying the person too much
+        if let Some(&overflow) = self.overflows.get(person) {
+            let (description, title) = if overflow < Decimal::ZERO {
+                (format!("{} {:8.2} credit", person, -overflow), "Credit")
+            } else {
+                (
+                    format!("{} {:8.2} overflow", person, overflow),
+                    "Unassigned payments",
+                )
+            };
+            items.push(LedgerItem {
+                description,
+                title: title.to_string(),
+                date: None,
+                id: None,
+                they_owe: (-overflow).max(Decimal::ZERO),
+                you_owe: overflow.max(Decimal::ZERO),
+            });
+        }
+
+        Ledger {
+            person: person.to_string(),
+            items,
+        }
+    }
+
+    pub fn ledgers(&self) -> Vec<Ledger> {
+        let all_people: BTreeSet<_> = self.all_people().collect();
+        all_people
+            .into_iter()
+            .map(|person| self.ledger(&person))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{add_advancement, add_debt, add_group, amount};
+
+    use super::*;
+
+    /// what each item of the ledger says the person and the user owe
+    fn positions(ledger: &Ledger) -> Vec<(String, Decimal, Decimal)> {
+        ledger
+            .items
+            .iter()
+            .map(|item| (item.title.clone(), item.they_owe, item.you_owe))
+            .collect()
+    }
+
+    #[test]
+    fn group_parts_are_owed_to_the_user() {
+        let mut expenses = Expenses::default();
+        add_group(
+            &mut expenses,
+            "2026-03-01",
+            "Dinner",
+            "10",
+            &[("Anna", "12"), ("Bob", "8")],
+            None,
+        );
+        add_group(
+            &mut expenses,
+            "2026-03-02",
+            "Lunch",
+            "5",
+            &[("Anna", "5")],
+            None,
+        );
+        add_group(
+            &mut expenses,
+            "2026-03-03",
+            "Cinema",
+            "9",
+            &[("Anna", "9")],
+            None,
+        );
+        expenses.group_expenses[1].paid_amounts[0] = Some(amount("2"));
+        expenses.group_expenses[2].paid_amounts[0] = Some(amount("12"));
+
+        let ledger = expenses.ledger("Anna");
+        assert_eq!(
+            positions(&ledger),
+            [
+                ("Dinner".to_string(), amount("12"), Decimal::ZERO),
+                ("Lunch".to_string(), amount("3"), Decimal::ZERO),
+                ("Cinema".to_string(), Decimal::ZERO, amount("3")),
+            ]
+        );
+        assert_eq!(ledger.balance(), amount("12"));
+        assert_eq!(expenses.ledger("Bob").balance(), amount("8"));
+    }
+
+    #[test]
+    fn the_user_owes_their_share_to_the_payer() {
+        let mut expenses = Expenses::default();
+        add_group(
+            &mut expenses,
+            "2026-03-01",
+            "Dinner",
+            "10",
+            &[("Anna", "20")],
+            Some("Anna"),
+        );
+
+        let ledger = expenses.ledger("Anna");
+        assert_eq!(
+            positions(&ledger),
+            [("Dinner".to_string(), Decimal::ZERO, amount("10"))]
+        );
+        assert_eq!(ledger.balance(), amount("-10"));
+    }
+
+    #[test]
+    fn debts_and_advancements_are_positions() {
+        let mut expenses = Expenses::default();
+        add_debt(&mut expenses, "2026-03-01", "Anna", "15");
+        add_advancement(&mut expenses, "2026-03-02", "Anna", "40");
+        add_debt(&mut expenses, "2026-03-03", "Bob", "7");
+
+        let ledger = expenses.ledger("Anna");
+        assert_eq!(
+            positions(&ledger),
+            [
+                ("Food".to_string(), Decimal::ZERO, amount("15")),
+                ("Advancement".to_string(), amount("40"), Decimal::ZERO),
+            ]
+        );
+        assert_eq!(ledger.balance(), amount("25"));
+        let people: Vec<_> = expenses
+            .ledgers()
+            .into_iter()
+            .map(|ledger| (ledger.person.clone(), ledger.balance()))
+            .collect();
+        assert_eq!(
+            people,
+            [
+                ("Anna".to_string(), amount("25")),
+                ("Bob".to_string(), amount("-7"))
+            ]
+        );
+    }
+
+    #[test]
+    fn a_negative_overflow_is_a_credit() {
+        let mut expenses = Expenses::default();
+        add_debt(&mut expenses, "2026-03-01", "Anna", "15");
+        expenses.overflows.insert("Anna".to_string(), amount("-4"));
+        expenses.overflows.insert("Bob".to_string(), amount("3"));
+
+        let ledger = expenses.ledger("Anna");
+        assert_eq!(
+            positions(&ledger)[1],
+            ("Credit".to_string(), amount("4"), Decimal::ZERO)
+        );
+        assert_eq!(ledger.items[1].description, "Anna     4.00 credit");
+        assert_eq!(ledger.balance(), amount("-11"));
+        assert_eq!(
+            positions(&expenses.ledger("Bob")),
+            [(
+                "Unassigned payments".to_string(),
+                Decimal::ZERO,
+                amount("3")
+            )]
+        );
+    }
+}
@@ -0,0 +1,612 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Display,
+};
+
+use chrono::{Local, NaiveDate};
+use chronoutil::RelativeDuration;
+use clap::{Subcommand, ValueEnum};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use self::audit::AuditLog;
+use self::errors::KakeboError;
+use self::journal::Journal;
+use expenses::{
+    advancement::Advancement,
+    category::{self, Categories},
+    debt::Debt,
+    group_expense::GroupExpense,
+    recurring_expense::RecurringExpense,
+    repayment::Repayment,
+    single_expense::SingleExpense,
+    ExpenseInfo, RecordId,
+};
+
+pub mod allocation;
+pub mod audit;
+pub mod errors;
+pub mod expenses;
+pub mod journal;
+pub mod ledger;
+pub mod prompter;
+pub mod qr;
+pub mod report;
+pub mod search;
+pub mod statement;
+pub mod storage;
+#[cfg(test)]
+mod testing;
+pub mod upcoming;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KakeboConfig {
+    pub currency: char,
+    pub decimal_sep: char,
+    pub user_name: String,
+    /// how people can pay the user back, added to statements
+    #[serde(default)]
+    pub payment_instructions: Option<String>,
+    /// the user's bank account for payment QR codes
+    #[serde(default)]
+    pub iban: Option<String>,
+    #[serde(default)]
+    pub bic: Option<String>,
+}
+
+impl Default for KakeboConfig {
+    fn default() -> Self {
+        Self {
+            currency: '€',
+            decimal_sep: '.',
+            user_name: "Friedrich".to_string(),
+            payment_instructions: None,
+            iban: None,
+            bic: None,
+        }
+    }
+}
+
+#[derive(Subcommand, ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpenseType {
+    Single,
+    Group,
+    Recurring,
+    Todo,
+    Advance,
+}
+
+impl Display for ExpenseType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            ExpenseType::Single => "single",
+            ExpenseType::Group => "group",
+            ExpenseType::Recurring => "recurring",
+            ExpenseType::Todo => "debt",
+            ExpenseType::Advance => "advance",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Expenses {
+    pub config: KakeboConfig,
+    pub single_expenses: Vec<SingleExpense>,
+    pub group_expenses: Vec<GroupExpense>,
+    pub recurring_expenses: Vec<RecurringExpense>,
+    pub debts_owed: Vec<Debt>,
+    pub unpaid_advancements: Vec<Advancement>,
+    pub overflows: HashMap<String, Decimal>,
+    #[serde(default)]
+    pub categories: Categories,
+    #[serde(default)]
+    pub last_id: RecordId,
+    #[serde(default)]
+    pub journal: Journal,
+    #[serde(default)]
+    pub history: AuditLog,
+}
+
+impl Expenses {
+    pub fn all_people(&self) -> impl Iterator<Item = String> + use<'_> {
+        self.group_expenses
+            .iter()
+            .flat_map(|group| group.people.iter())
+            .chain(self.debts_owed.iter().map(|debt| &debt.person))
+            .chain(self.unpaid_advancements.iter().map(|advc| &advc.person))
+            .chain(self.overflows.keys())
+            .map(String::clone)
+    }
+
+    pub fn infos_mut(&mut self) -> impl Iterator<Item = &mut ExpenseInfo> + use<'_> {
+        self.single_expenses
+            .iter_mut()
+            .map(|single| &mut single.info)
+            .chain(self.group_expenses.iter_mut().map(|group| &mut group.info))
+            .chain(self.recurring_expenses.iter_mut().map(|rec| &mut rec.info))
+            .chain(
+                self.debts_owed
+                    .iter_mut()
+                    .map(|debt| &mut debt.expense.info),
+            )
+    }
+
+    /// hands out a fresh ID that no other record uses
+    pub fn new_id(&mut self) -> RecordId {
+        self.last_id = self.last_id.next();
+        self.last_id
+    }
+
+    fn ids_mut(&mut self) -> impl Iterator<Item = &mut RecordId> + use<'_> {
+        self.single_expenses
+            .iter_mut()
+            .map(|single| &mut single.info.id)
+            .chain(
+                self.group_expenses
+                    .iter_mut()
+                    .map(|group| &mut group.info.id),
+            )
+            .chain(
+                self.recurring_expenses
+                    .iter_mut()
+                    .map(|rec| &mut rec.info.id),
+            )
+            .chain(
+                self.debts_owed
+                    .iter_mut()
+                    .map(|debt| &mut debt.expense.info.id),
+            )
+            .chain(self.unpaid_advancements.iter_mut().map(|advc| &mut advc.id))
+    }
+
+    /// gives every record of an older database an ID, in the order they are stored
+    pub fn assign_missing_ids(&mut self) {
+        let highest = self.ids_mut().map(|id| *id).max().unwrap_or_default();
+        self.last_id = self.last_id.max(highest);
+        let mut last_id = self.last_id;
+        for id in self.ids_mut().filter(|id| !id.is_assigned()) {
+            last_id = last_id.next();
+            *id = last_id;
+        }
+        self.last_id = last_id;
+    }
+
+    /// records the occurrences of recurring expenses that are due by `today`,
+    /// shared ones become group expenses, returns whether there were any
+    pub fn materialize_occurrences(&mut self, today: NaiveDate) -> bool {
+        let mut recorded = false;
+        let mut last_id = self.last_id;
+        let mut group_expenses = Vec::new();
+        for recurring in self.recurring_expenses.iter_mut() {
+            let first_new = recurring.occurrences.len();
+            recurring.materialize(today);
+            recorded |= recurring.occurrences.len() > first_new;
+            for index in first_new..recurring.occurrences.len() {
+                let id = last_id.next();
+                if let Some(group_expense) =
+                    recurring.group_expense(&recurring.occurrences[index], id)
+                {
+                    last_id = id;
+                    recurring.occurrences[index].group_id = Some(id);
+                    group_expenses.push(group_expense);
+                }
+            }
+        }
+        self.last_id = last_id;
+        self.group_expenses.extend(group_expenses);
+        recorded
+    }
+
+    /// finds the type of the record with the given ID and its position in the list of that type
+    pub fn locate(&self, id: RecordId) -> Option<(ExpenseType, usize)> {
+        fn position<T: DisplayableExpense>(records: &[T], id: RecordId) -> Option<usize> {
+            records.iter().position(|record| record.id() == id)
+        }
+        position(&self.single_expenses, id)
+            .map(|idx| (ExpenseType::Single, idx))
+            .or_else(|| position(&self.group_expenses, id).map(|idx| (ExpenseType::Group, idx)))
+            .or_else(|| {
+                position(&self.recurring_expenses, id).map(|idx| (ExpenseType::Recurring, idx))
+            })
+            .or_else(|| position(&self.debts_owed, id).map(|idx| (ExpenseType::Todo, idx)))
+            .or_else(|| {
+                position(&self.unpaid_advancements, id).map(|idx| (ExpenseType::Advance, idx))
+            })
+    }
+
+    /// records a repayment of a debt, a fully paid debt becomes a regular expense
+    pub fn repay_debt(&mut self, idx: usize, repayment: Repayment) {
+        let debt = &mut self.debts_owed[idx];
+        debt.repayments.push(repayment);
+        if debt.outstanding() <= Decimal::ZERO {
+            let debt_paid = self.debts_owed.remove(idx);
+            self.single_expenses.push(debt_paid.expense);
+        }
+    }
+
+    /// records a repayment of an advancement, a fully paid advancement is removed
+    pub fn repay_advancement(&mut self, idx: usize, repayment: Repayment) {
+        let advancement = &mut self.unpaid_advancements[idx];
+        advancement.repayments.push(repayment);
+        if advancement.outstanding() <= Decimal::ZERO {
+            self.unpaid_advancements.remove(idx);
+        }
+    }
+
+    pub fn locate_or_err(&self, id: RecordId) -> Result<(ExpenseType, usize), KakeboError> {
+        self.locate(id)
+            .ok_or_else(|| KakeboError::InvalidArgument(format!("No record with ID {}", id)))
+    }
+
+    pub fn infos(&self) -> impl Iterator<Item = &ExpenseInfo> + use<'_> {
+        self.single_expenses
+            .iter()
+            .map(|single| &single.info)
+            .chain(self.group_expenses.iter().map(|group| &group.info))
+            .chain(self.recurring_expenses.iter().map(|rec| &rec.info))
+            .chain(self.debts_owed.iter().map(|debt| &debt.expense.info))
+    }
+
+    /// makes sure every category used by an expense can be selected again
+    pub fn register_used_categories(&mut self) {
+        let used: Vec<_> = self.infos().map(|info| info.category.clone()).collect();
+        for category in &used {
+            self.categories.register(category);
+        }
+    }
+
+    /// moves every expense of the category `from` or its subcategories below `to`
+    fn recategorize(&mut self, from: &str, to: &str) -> usize {
+        let mut changed = 0;
+        for info in self.infos_mut() {
+            if let Some(category) = info.category.moved(from, to) {
+                info.category = category;
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    pub fn rename_category(&mut self, old: &str, new: &str) -> Result<usize, KakeboError> {
+        let (old, new) = (category::normalize(old), category::validate(new)?);
+        self.categories.rename(&old, &new)?;
+        Ok(self.recategorize(&old, &new))
+    }
+
+    pub fn merge_category(&mut self, from: &str, into: &str) -> Result<usize, KakeboError> {
+        let (from, into) = (category::normalize(from), category::normalize(into));
+        self.categories.merge(&from, &into)?;
+        Ok(self.recategorize(&from, &into))
+    }
+
+    pub fn remove_category(&mut self, name: &str) -> Result<(), KakeboError> {
+        let in_use = self.infos().any(|info| info.category.is_within(name));
+        if in_use {
+            return Err(KakeboError::InvalidArgument(format!(
+                "Category {} is still used by expenses, merge it into another category instead",
+                name
+            )));
+        }
+        self.categories.remove(&category::normalize(name))
+    }
+
+    pub fn all_tags(&self) -> impl Iterator<Item = String> + use<'_> {
+        self.infos()
+            .flat_map(|info| info.tags.iter())
+            .map(String::clone)
+    }
+
+    /// what the user spent in the month up to today
+    pub fn status(&self) -> Status {
+        let today = Local::now().date_naive();
+        let month_ago = today - RelativeDuration::months(1);
+        let last_month = |date: NaiveDate| date > month_ago && date <= today;
+
+        Status {
+            single_last_month: self
+                .single_expenses
+                .iter()
+                .filter(|expense| last_month(expense.info.date))
+                .map(|expense| expense.amount)
+                .sum(),
+            group_last_month: self
+                .group_expenses
+                .iter()
+                .filter(|expense| last_month(expense.info.date))
+                .map(GroupExpense::true_user_amount)
+                .sum(),
+            recurring_last_month: self
+                .recurring_expenses
+                .iter()
+                .map(|expense| expense.amount_in_interval(month_ago, today))
+                .sum(),
+        }
+    }
+
+    /// caps what people paid for group expenses at their share and keeps the rest as overflow
+    pub fn sanitize(&mut self) {
+        let paid_by_user = self
+            .group_expenses
+            .iter_mut()
+            .filter(|group_expense| group_expense.paid_by_user());
+        for group_expense in paid_by_user {
+            for (i, to_pay) in group_expense.true_amounts().into_iter().enumerate() {
+                let person = group_expense.people[i].clone();
+                let paid = group_expense.paid_amounts[i].unwrap_or(Decimal::ZERO);
+                if paid > to_pay {
+                    group_expense.paid_amounts[i] = Some(to_pay);
+                    let overflow = paid - to_pay;
+                    self.overflows
+                        .entry(person)
+                        .and_modify(|mut v| v += overflow)
+                        .or_insert(overflow);
+                }
+            }
+        }
+    }
+}
+
+/// what the user spent recently, by type of expense
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Status {
+    pub single_last_month: Decimal,
+    pub group_last_month: Decimal,
+    pub recurring_last_month: Decimal,
+}
+
+impl Status {
+    pub fn total_last_month(&self) -> Decimal {
+        self.single_last_month + self.group_last_month + self.recurring_last_month
+    }
+}
+
+#[derive(Debug)]
+pub struct Environment {
+    pub people: BTreeSet<String>,
+    pub tags: BTreeSet<String>,
+    pub categories: Vec<String>,
+}
+
+pub const ANSI_RED: &str = "\x1b[31m";
+pub const ANSI_GREEN: &str = "\x1b[32m";
+pub const ANSI_STOP: &str = "\x1b[0m";
+
+pub trait DisplayableExpense: Display + Eq {
+    fn name() -> &'static str;
+    fn plural_name() -> &'static str;
+    fn id(&self) -> RecordId;
+    fn set_id(&mut self, id: RecordId);
+    /// the record with everything the configuration adds to it, e.g. the currency
+    fn configured_display(&self, _config: &KakeboConfig) -> String {
+        self.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prompter::ScriptedPrompter;
+    use crate::testing::{
+        add_advancement, add_debt, add_group, add_single, amount, date, environment,
+    };
+
+    use super::*;
+
+    #[test]
+    fn all_tags_covers_every_expense_with_info() {
+        let mut expenses = Expenses::default();
+        add_single(
+            &mut expenses,
+            "2026-03-01",
+            "Food",
+            "Lunch #work #team",
+            "12",
+        );
+        add_single(&mut expenses, "2026-03-02", "Food", "Dinner #work", "20");
+        add_group(
+            &mut expenses,
+            "2026-03-03",
+            "Pizza #party",
+            "10",
+            &[("Anna", "15")],
+            None,
+        );
+        add_debt(&mut expenses, "2026-03-04", "Bob", "30");
+        expenses.debts_owed[0]
+            .expense
+            .info
+            .tags
+            .insert("loan".to_string());
+
+        let tags: BTreeSet<_> = expenses.all_tags().collect();
+        assert_eq!(
+            tags.into_iter().collect::<Vec<_>>(),
+            ["loan", "party", "team", "work"]
+        );
+    }
+
+    #[test]
+    fn rename_category_updates_expenses_in_subcategories() {
+        let mut expenses = Expenses::default();
+        expenses.categories.add("Food > Groceries").unwrap();
+        add_single(
+            &mut expenses,
+            "2026-03-01",
+            "Food > Groceries",
+            "Market",
+            "20",
+        );
+        add_single(&mut expenses, "2026-03-02", "Food", "Lunch", "12");
+        add_single(&mut expenses, "2026-03-03", "Hobby", "Climbing", "45");
+        expenses.register_used_categories();
+
+        assert_eq!(expenses.rename_category("Food", " Meals ").unwrap(), 2);
+        let categories: Vec<_> = expenses
+            .single_expenses
+            .iter()
+            .map(|single| single.info.category.to_string())
+            .collect();
+        assert_eq!(categories, ["Meals > Groceries", "Meals", "Hobby"]);
+        assert!(expenses.categories.contains("Meals > Groceries"));
+
+        assert!(expenses.rename_category("Meals", "").is_err());
+        assert!(expenses.rename_category("Meals", "Meals >> Old").is_err());
+        assert_eq!(expenses.single_expenses[1].info.category.name(), "Meals");
+    }
+
+    #[test]
+    fn merge_category_moves_expenses_and_rejects_its_own_subtree() {
+        let mut expenses = Expenses::default();
+        add_single(
+            &mut expenses,
+            "2026-03-01",
+            "Food > Groceries",
+            "Market",
+            "20",
+        );
+        add_single(
+            &mut expenses,
+            "2026-03-02",
+            "Snacks > Sweet",
+            "Chocolate",
+            "2",
+        );
+        expenses.register_used_categories();
+
+        assert!(expenses.merge_category("Food", "Food > Groceries").is_err());
+        assert_eq!(
+            expenses.single_expenses[0].info.category.name(),
+            "Food > Groceries"
+        );
+
+        assert_eq!(expenses.merge_category("Snacks", "Food").unwrap(), 1);
+        assert_eq!(
+            expenses.single_expenses[1].info.category.name(),
+            "Food > Sweet"
+        );
+        assert!(!expenses.categories.covers("Snacks"));
+    }
+
+    fn repayment(day: u32, value: &str) -> Repayment {
+        Repayment {
+            date: date(2026, 3, day),
+            amount: amount(value),
+        }
+    }
+
+    #[test]
+    fn a_debt_is_repaid_in_instalments() {
+        let mut expenses = Expenses::default();
+        let id = add_debt(&mut expenses, "2026-03-01", "Anna", "50");
+
+        expenses.repay_debt(0, repayment(5, "20"));
+        expenses.repay_debt(0, repayment(10, "10"));
+        assert_eq!(expenses.debts_owed[0].outstanding(), amount("20"));
+        assert_eq!(expenses.ledger("Anna").you_owe(), amount("20"));
+        assert!(expenses.single_expenses.is_empty());
+
+        expenses.repay_debt(0, repayment(15, "20"));
+        assert!(expenses.debts_owed.is_empty());
+        assert_eq!(expenses.single_expenses[0].info.id, id);
+        assert_eq!(expenses.single_expenses[0].amount, amount("50"));
+        assert!(expenses.ledger("Anna").items.is_empty());
+    }
+
+    #[test]
+    fn an_advancement_is_repaid_in_instalments() {
+        let mut expenses = Expenses::default();
+        add_advancement(&mut expenses, "2026-03-01", "Anna", "40");
+
+        expenses.repay_advancement(0, repayment(5, "15"));
+        assert_eq!(expenses.unpaid_advancements[0].outstanding(), amount("25"));
+        assert_eq!(expenses.ledger("Anna").they_owe(), amount("25"));
+
+        expenses.repay_advancement(0, repayment(10, "25"));
+        assert!(expenses.unpaid_advancements.is_empty());
+    }
+
+    #[test]
+    fn a_repayment_cannot_exceed_what_is_outstanding() {
+        let mut prompter = ScriptedPrompter::new(["2026-03-05", "30"]);
+        assert!(matches!(
+            Repayment::new(&mut prompter, amount("25")),
+            Err(KakeboError::InvalidArgument(message)) if message.ends_with("Only 25.00 is outstanding.")
+        ));
+        let mut prompter = ScriptedPrompter::new(["2026-03-05", "10"]);
+        let repayment = Repayment::new(&mut prompter, amount("25")).unwrap();
+        assert_eq!(repayment, self::repayment(5, "10"));
+    }
+
+    /// rent of 1000 shared with Anna from `first` on, the user pays 400 of it
+    fn add_shared_rent(expenses: &mut Expenses, first: &str) {
+        let mut prompter = ScriptedPrompter::new([
+            first,
+            "Rent",
+            "",
+            "",
+            "y",
+            "exact amounts",
+            "400",
+            "Anna",
+            "600",
+            "<esc>",
+            "Friedrich",
+            "Month(s)",
+            "1",
+            "n",
+            "y",
+        ]);
+        let mut rent =
+            RecurringExpense::new(&mut prompter, &environment("Rent"), &expenses.config).unwrap();
+        rent.set_id(expenses.new_id());
+        expenses.recurring_expenses.push(rent);
+    }
+
+    #[test]
+    fn shared_occurrences_become_group_expenses() {
+        let mut expenses = Expenses::default();
+        add_shared_rent(&mut expenses, "2026-01-01");
+
+        assert!(expenses.materialize_occurrences(date(2026, 1, 1)));
+        assert!(expenses.materialize_occurrences(date(2026, 3, 5)));
+        assert!(!expenses.materialize_occurrences(date(2026, 3, 5)));
+
+        let rent = &expenses.recurring_expenses[0];
+        let group_ids: Vec<_> = rent
+            .occurrences
+            .iter()
+            .map(|occurrence| occurrence.group_id.unwrap())
+            .collect();
+        let created: Vec<_> = expenses
+            .group_expenses
+            .iter()
+            .map(|group| (group.info.id, group.info.date, group.true_user_amount()))
+            .collect();
+        assert_eq!(
+            created,
+            [
+                (group_ids[0], date(2026, 1, 1), amount("400")),
+                (group_ids[1], date(2026, 2, 1), amount("400")),
+                (group_ids[2], date(2026, 3, 1), amount("400")),
+            ]
+        );
+        assert_eq!(expenses.ledger("Anna").balance(), amount("1800"));
+        // the user's shares are counted once, as group expenses
+        assert_eq!(
+            rent.amount_in_interval(date(2026, 1, 1), date(2026, 3, 31)),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn no_group_expenses_are_created_for_the_past() {
+        let mut expenses = Expenses::default();
+        add_shared_rent(&mut expenses, "2025-01-01");
+
+        assert!(!expenses.materialize_occurrences(date(2026, 3, 5)));
+        assert!(expenses.group_expenses.is_empty());
+        assert!(expenses.materialize_occurrences(date(2026, 4, 1)));
+        assert_eq!(expenses.group_expenses.len(), 1);
+        assert_eq!(expenses.ledger("Anna").balance(), amount("600"));
+    }
+}
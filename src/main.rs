@@ -1,47 +1,33 @@
 use std::{
-    cmp::Ordering,
-    collections::{BTreeSet, HashMap},
     fmt::Display,
-    fs::File,
     path::{Path, PathBuf},
     process::ExitCode,
 };
 
-use age::{secrecy::Secret, Decryptor, Encryptor};
-use chrono::Local;
-use chronoutil::RelativeDuration;
+use chrono::{Local, NaiveDate};
 use clap::{Parser, Subcommand};
-use inquire::{Confirm, Password, Select};
-use lz4_flex::frame::{FrameDecoder, FrameEncoder};
+use inquire::Password;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 
-use self::errors::KakeboError;
-use expenses::{
-    advancement::Advancement, debt::Debt, group_expense::GroupExpense,
-    recurring_expense::RecurringExpense, single_expense::SingleExpense,
+use kakebo::{
+    allocation::{Direction, Strategy},
+    errors::KakeboError,
+    expenses::{
+        advancement::Advancement, debt::Debt, group_expense::GroupExpense,
+        recurring_expense::RecurringExpense, repayment::Repayment, single_expense::SingleExpense,
+        RecordId,
+    },
+    prompter::{choose, InquirePrompter, Prompter, ScriptedPrompter},
+    qr,
+    report::{self, Grouping, ReportOptions},
+    search::{self, SearchFilter},
+    statement::StatementFormat,
+    storage, upcoming, DisplayableExpense, Environment, ExpenseType, Expenses, KakeboConfig,
 };
 
-mod errors;
-mod expenses;
-
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
-pub struct KakeboConfig {
-    pub currency: char,
-    pub decimal_sep: char,
-    pub user_name: String,
-}
-
-impl Default for KakeboConfig {
-    fn default() -> Self {
-        Self {
-            currency: '€',
-            decimal_sep: '.',
-            user_name: "Friedrich".to_string(),
-        }
-    }
-}
+mod cli;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -50,6 +36,10 @@ struct Args {
     command: Command,
     #[arg(short, long)]
     debug: bool,
+    /// answer the prompts from this file, one answer per line, instead of asking,
+    /// the database password is still asked in the terminal
+    #[arg(long)]
+    answers: Option<PathBuf>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -64,32 +54,129 @@ enum Command {
     List {
         person: String,
     },
+    /// show a payment QR code for what a person owes you, or write it to a .png or .svg file
+    Qr {
+        person: String,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// print a message for a person listing everything that is open between you
+    Statement {
+        person: String,
+        #[arg(short, long, value_enum, default_value_t)]
+        format: StatementFormat,
+    },
     Add {
         #[command(subcommand)]
         expense_type: ExpenseType,
     },
+    #[command(args_conflicts_with_subcommands = true, arg_required_else_help = true)]
     Edit {
         #[command(subcommand)]
-        expense_type: ExpenseType,
+        expense_type: Option<ExpenseType>,
+        /// the ID of the record to edit
+        id: Option<RecordId>,
     },
+    #[command(args_conflicts_with_subcommands = true, arg_required_else_help = true)]
     Delete {
         #[command(subcommand)]
-        expense_type: ExpenseType,
+        expense_type: Option<ExpenseType>,
+        /// the ID of the record to delete
+        id: Option<RecordId>,
+    },
+    Show {
+        id: RecordId,
     },
     Receive {
         value: Decimal,
         from: Option<String>,
+        /// how to spread the money over the open items
+        #[arg(short, long, value_enum, default_value_t)]
+        strategy: Strategy,
+    },
+    /// pay back money you owe someone
+    Pay {
+        value: Decimal,
+        to: Option<String>,
+        /// how to spread the money over what you owe
+        #[arg(short, long, value_enum, default_value_t)]
+        strategy: Strategy,
     },
     Sanitize,
+    Search {
+        #[command(flatten)]
+        filter: SearchFilter,
+        /// group the totals by category or tag instead of by type
+        #[arg(long, value_enum)]
+        by: Option<Grouping>,
+    },
+    Category {
+        #[command(subcommand)]
+        action: CategoryAction,
+    },
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// list the recurring expenses due in the coming days and forecast next month
+    Upcoming {
+        /// how many days to look ahead
+        #[arg(short, long, default_value_t = 30)]
+        days: u32,
+    },
+    Report {
+        #[command(flatten)]
+        options: ReportOptions,
+    },
+    /// revert the last operation
+    Undo,
+    /// apply the last undone operation again
+    Redo,
+    /// list the most recent operations
+    Log {
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+    },
+    /// show the audit trail of all changes
+    History {
+        /// only show the changes to this record
+        #[arg(long)]
+        record: Option<RecordId>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
-enum ExpenseType {
-    Single,
-    Group,
-    Recurring,
-    Todo,
-    Advance,
+enum CategoryAction {
+    List,
+    Add {
+        name: String,
+    },
+    /// rename a category, including all expenses that use it
+    Rename {
+        old: String,
+        new: String,
+    },
+    /// move all expenses of one category into another and remove the first one
+    Merge {
+        from: String,
+        into: String,
+    },
+    /// remove a category that is not used by any expense
+    Remove {
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// show the current configuration
+    Show,
+    /// set how people can pay you back, e.g. your bank details, or remove them if omitted
+    PaymentInstructions { instructions: Option<String> },
+    /// set the IBAN used for payment QR codes, or remove it if omitted
+    Iban { iban: Option<String> },
+    /// set the BIC used for payment QR codes, or remove it if omitted
+    Bic { bic: Option<String> },
 }
 
 #[derive(Subcommand, Debug)]
@@ -98,253 +185,188 @@ enum IncomeType {
     Recurring,
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Eq)]
-struct Expenses {
-    config: KakeboConfig,
-    single_expenses: Vec<SingleExpense>,
-    group_expenses: Vec<GroupExpense>,
-    recurring_expenses: Vec<RecurringExpense>,
-    debts_owed: Vec<Debt>,
-    unpaid_advancements: Vec<Advancement>,
-    overflows: HashMap<String, Decimal>,
+/// lets the user pick a record and returns its position
+fn select<T: DisplayableExpense>(
+    prompter: &mut dyn Prompter,
+    records: &[T],
+    verb: &str,
+) -> Result<Option<usize>, KakeboError> {
+    if records.is_empty() {
+        println!("No {} to {}.", T::plural_name(), verb);
+        return Ok(None);
+    }
+    let options: Vec<_> = records.iter().rev().collect();
+    let selected = choose(
+        prompter,
+        &format!("Which {} do you want to {}?", T::name(), verb),
+        options,
+    )?;
+    let idx = records
+        .iter()
+        .position(|record| record.id() == selected.id())
+        .unwrap_or_else(|| panic!("Selected {} must exist.", T::name()));
+    Ok(Some(idx))
 }
 
-impl Expenses {
-    pub fn all_people(&self) -> impl Iterator<Item = String> + use<'_> {
-        self.group_expenses
-            .iter()
-            .flat_map(|group| group.people.iter())
-            .chain(self.debts_owed.iter().map(|debt| &debt.person))
-            .chain(self.unpaid_advancements.iter().map(|advc| &advc.person))
-            .chain(self.overflows.keys())
-            .map(String::clone)
+fn select_record(
+    prompter: &mut dyn Prompter,
+    expenses: &Expenses,
+    expense_type: ExpenseType,
+    verb: &str,
+) -> Result<Option<usize>, KakeboError> {
+    match expense_type {
+        ExpenseType::Single => select(prompter, &expenses.single_expenses, verb),
+        ExpenseType::Group => select(prompter, &expenses.group_expenses, verb),
+        ExpenseType::Recurring => select(prompter, &expenses.recurring_expenses, verb),
+        ExpenseType::Todo => select(prompter, &expenses.debts_owed, verb),
+        ExpenseType::Advance => select(prompter, &expenses.unpaid_advancements, verb),
     }
+}
 
-    pub fn print_status(&self) {
-        println!("Expenses Overview:");
-        let today = Local::now().date_naive();
-        let month_ago = today - RelativeDuration::months(1);
-
-        let single_expenses_last_month: Decimal = self
-            .single_expenses
-            .iter()
-            .filter(|expense| expense.info.date > month_ago && expense.info.date <= today)
-            .map(|expense| expense.amount)
-            .sum();
-        let group_expenses_last_month: Decimal = self
-            .group_expenses
-            .iter()
-            .filter(|expense| expense.info.date > month_ago && expense.info.date <= today)
-            .map(GroupExpense::true_user_amount)
-            .sum::<Decimal>();
-        let recurring_expenses_last_month: Decimal = self
-            .recurring_expenses
-            .iter()
-            .map(|expense| expense.amount_in_interval(month_ago, today))
-            .sum::<Decimal>();
-        println!(
-            "  Single Expenses last month:    {:8.2}",
-            single_expenses_last_month
-        );
-        println!(
-            "  Group Expenses last month:     {:8.2}",
-            group_expenses_last_month
-        );
-        println!(
-            "  Recurring Expenses last month: {:8.2}",
-            recurring_expenses_last_month
-        );
-        println!(
-            "  Total Expenses last month:     {:8.2}",
-            single_expenses_last_month + group_expenses_last_month + recurring_expenses_last_month
-        );
-
-        println!("Balances:");
-        let mut people_owe_user: HashMap<&str, Decimal> = HashMap::new();
-        let mut user_owes_people: HashMap<&str, Decimal> = HashMap::new();
-
-        let debts_from_group_expenses = self.group_expenses.iter().flat_map(|group_expense| {
-            group_expense.people.iter().zip(
-                group_expense
-                    .true_amounts()
-                    .into_iter()
-                    .zip(group_expense.paid_amounts.iter()),
-            )
-        });
-        for (person, (amount, already_paid)) in debts_from_group_expenses {
-            let paid = already_paid.unwrap_or(Decimal::ZERO);
-            match paid.cmp(&amount) {
-                Ordering::Less => {
-                    let owed: Decimal = amount - paid;
-                    people_owe_user
-                        .entry(person)
-                        .and_modify(|val| *val += owed)
-                        .or_insert(owed);
-                }
-                Ordering::Greater => {
-                    let overpaid: Decimal = paid - amount;
-                    user_owes_people
-                        .entry(person)
-                        .and_modify(|val| *val += overpaid)
-                        .or_insert(overpaid);
-                }
-                Ordering::Equal => {}
-            }
-        }
-        for debt in &self.debts_owed {
-            user_owes_people
-                .entry(&debt.person)
-                .and_modify(|val| *val += debt.expense.amount)
-                .or_insert(debt.expense.amount);
-        }
-        for advancement in &self.unpaid_advancements {
-            people_owe_user
-                .entry(&advancement.person)
-                .and_modify(|val| *val += advancement.amount)
-                .or_insert(advancement.amount);
-        }
-        for (person, overflow) in &self.overflows {
-            user_owes_people
-                .entry(person)
-                .and_modify(|val| *val += overflow)
-                .or_insert(*overflow);
-        }
-
-        let all_people: BTreeSet<_> = self.all_people().collect();
-        println!("             they owe   you owe          balance");
-        for person in all_people {
-            let good = people_owe_user
-                .get(person.as_str())
-                .map_or(Decimal::ZERO, |r| *r);
-            let bad = user_owes_people
-                .get(person.as_str())
-                .map_or(Decimal::ZERO, |r| *r);
-            let balance = good - bad;
-            let balance_color = if Decimal::abs(&balance) >= Decimal::from(5) {
-                ANSI_RED
-            } else {
-                ANSI_GREEN
-            };
-            println!(
-                "  {:10} {:8.2}, {:8.2}, TOTAL: {balance_color}{:+8.2}{ANSI_STOP}",
-                person, good, bad, balance
-            );
+/// the record given by its ID or, if there is none, selected from the records of the given type
+fn target_record(
+    prompter: &mut dyn Prompter,
+    expenses: &Expenses,
+    id: Option<RecordId>,
+    expense_type: Option<ExpenseType>,
+    verb: &str,
+) -> Result<Option<(ExpenseType, usize)>, KakeboError> {
+    match (id, expense_type) {
+        (Some(id), _) => expenses.locate_or_err(id).map(Some),
+        (None, Some(expense_type)) => {
+            let idx = select_record(prompter, expenses, expense_type, verb)?;
+            Ok(idx.map(|idx| (expense_type, idx)))
         }
+        (None, None) => Ok(None),
     }
 }
 
-#[derive(Debug)]
-pub struct Environment {
-    pub people: BTreeSet<String>,
-}
-
-fn parse_file<T>(path: &Path) -> Result<T, KakeboError>
-where
-    T: for<'de> Deserialize<'de> + Default,
-{
-    if !path.exists() {
-        println!("File {} does not exist.", path.display());
-        return Ok(T::default());
-    }
-    let passphrase = Password::new("Enter decryption password:")
-        .with_display_mode(inquire::PasswordDisplayMode::Hidden)
-        .without_confirmation()
-        .prompt()?;
-    let mut file = File::open(path)?;
-    let decryptor = match Decryptor::new(&mut file)? {
-        Decryptor::Passphrase(decr) => decr,
-        _ => unreachable!(),
+/// the person money is exchanged with, checking the given name or asking for one
+fn counterparty(
+    prompter: &mut dyn Prompter,
+    person: Option<String>,
+    value: Decimal,
+    (verb, preposition): (&str, &str),
+    question: &str,
+    environment: &Environment,
+) -> Result<String, KakeboError> {
+    let describe = |person: &str| format!("{} {} {} {}", verb, value, preposition, person);
+    let person = if let Some(person) = person {
+        if !environment.people.contains(&person) {
+            return Err(KakeboError::InvalidArgument(format!(
+                "Cannot {}, {} does not exist",
+                describe(&person),
+                person
+            )));
+        }
+        person
+    } else {
+        let options = environment.people.iter().map(|s| s.to_string()).collect();
+        choose(prompter, question, options)?
     };
-    let mut decrypt_reader = decryptor.decrypt(&Secret::new(passphrase.to_owned()), None)?;
-    let mut decode_reader = FrameDecoder::new(&mut decrypt_reader);
-    let expenses = rmp_serde::decode::from_read(&mut decode_reader)?;
-    println!("Expenses parsed from {}", path.display());
-    Ok(expenses)
+    if value < Decimal::ZERO {
+        return Err(KakeboError::InvalidArgument(format!(
+            "Cannot {}, {} is negative",
+            describe(&person),
+            value
+        )));
+    }
+    Ok(person)
 }
 
-fn write_file<T>(path: &Path, expenses: &T) -> Result<(), KakeboError>
-where
-    T: Serialize,
-{
-    let passphrase = Password::new("Enter encryption password:")
+/// asks for the password of the database
+///
+/// This always happens in the terminal, even with an answer file, so the password is never
+/// written down next to the answers.
+fn passphrase(prompt: &str) -> Result<String, KakeboError> {
+    Ok(Password::new(prompt)
         .with_display_mode(inquire::PasswordDisplayMode::Hidden)
         .without_confirmation()
-        .prompt()?;
-    let mut file = File::create(path)?;
-    let encryptor = Encryptor::with_user_passphrase(Secret::new(passphrase.to_owned()));
-    let mut encrypt_writer = encryptor.wrap_output(&mut file)?;
-    let mut compress_writer = FrameEncoder::new(&mut encrypt_writer);
-    rmp_serde::encode::write(&mut compress_writer, expenses)?;
-    compress_writer.finish()?;
-    encrypt_writer.finish()?;
-    Ok(())
+        .prompt()?)
 }
 
-#[allow(dead_code)]
-/// a function that simplifies tranforming older versions of the data structure into new ones
-fn transform<Src, Dst>(path: &Path) -> Result<(), KakeboError>
-where
-    Src: for<'de> Deserialize<'de> + Default,
-    Dst: Serialize + From<Src>,
-{
-    let from_content: Src = parse_file(path)?;
-    let to_content: Dst = from_content.into();
-    write_file(path, &to_content)
+fn show_record(expenses: &Expenses, expense_type: ExpenseType, idx: usize) {
+    let config = &expenses.config;
+    let details = match expense_type {
+        ExpenseType::Single => expenses.single_expenses[idx].configured_display(config),
+        ExpenseType::Group => expenses.group_expenses[idx].configured_display(config),
+        ExpenseType::Recurring => expenses.recurring_expenses[idx].configured_display(config),
+        ExpenseType::Todo => expenses.debts_owed[idx].configured_display(config),
+        ExpenseType::Advance => expenses.unpaid_advancements[idx].configured_display(config),
+    };
+    println!("{}", details);
 }
 
-pub const ANSI_RED: &str = "\x1b[31m";
-pub const ANSI_GREEN: &str = "\x1b[32m";
-pub const ANSI_STOP: &str = "\x1b[0m";
-
-trait DisplayableExpense: Display + Eq {
-    fn name() -> &'static str;
-    fn plural_name() -> &'static str;
-    fn configured_display(&self, _config: &KakeboConfig) {
-        println!("{}", self)
+fn delete<T: DisplayableExpense>(
+    prompter: &mut dyn Prompter,
+    records: &mut Vec<T>,
+    idx: usize,
+    config: &KakeboConfig,
+) -> Result<bool, KakeboError> {
+    println!("{}", records[idx].configured_display(config));
+    let deletion_confirmed = prompter.confirm(&format!(
+        "Are you sure you want to delete this {}?",
+        T::name()
+    ))?;
+    if deletion_confirmed {
+        records.remove(idx);
     }
+    Ok(deletion_confirmed)
 }
 
-fn status<T: DisplayableExpense>(expenses: &[T], config: &KakeboConfig) -> Result<(), KakeboError> {
-    if expenses.is_empty() {
-        println!("No {} to view.", T::plural_name());
-        return Ok(());
+fn delete_record(
+    prompter: &mut dyn Prompter,
+    expenses: &mut Expenses,
+    expense_type: ExpenseType,
+    idx: usize,
+) -> Result<bool, KakeboError> {
+    let config = &expenses.config;
+    match expense_type {
+        ExpenseType::Single => delete(prompter, &mut expenses.single_expenses, idx, config),
+        ExpenseType::Group => delete(prompter, &mut expenses.group_expenses, idx, config),
+        ExpenseType::Recurring => delete(prompter, &mut expenses.recurring_expenses, idx, config),
+        ExpenseType::Todo => delete(prompter, &mut expenses.debts_owed, idx, config),
+        ExpenseType::Advance => delete(prompter, &mut expenses.unpaid_advancements, idx, config),
     }
-    let options: Vec<_> = expenses.iter().rev().collect();
-    let to_view = Select::new(
-        format!("Which {} do you want to view?", T::name()).as_str(),
-        options,
-    )
-    .prompt()?;
-    to_view.configured_display(config);
-    Ok(())
 }
 
-fn delete<T: DisplayableExpense>(
-    expenses: &mut Vec<T>,
-    config: &KakeboConfig,
+fn edit_record(
+    prompter: &mut dyn Prompter,
+    expenses: &mut Expenses,
+    environment: &Environment,
+    expense_type: ExpenseType,
+    idx: usize,
 ) -> Result<bool, KakeboError> {
-    if expenses.is_empty() {
-        println!("No {} to delete.", T::plural_name());
-        return Ok(false);
-    }
-    let options: Vec<_> = expenses.iter().rev().collect();
-    let to_delete = Select::new(
-        format!("Which {} do you want to delete?", T::name()).as_str(),
-        options,
-    )
-    .prompt()?;
-    to_delete.configured_display(config);
-    let idx = expenses
-        .iter()
-        .position(|adv| adv == to_delete)
-        .unwrap_or_else(|| panic!("Selected {} must exist.", T::name()));
-    let deletion_confirmed =
-        Confirm::new(format!("Are you sure you want to delete this {}?", T::name()).as_str())
-            .prompt()?;
-    if deletion_confirmed {
-        expenses.remove(idx);
+    match expense_type {
+        ExpenseType::Single => {
+            let to_edit = &mut expenses.single_expenses[idx];
+            let changed = to_edit.edit(prompter, environment, &expenses.config)?;
+            expenses.categories.register(&to_edit.info.category);
+            Ok(changed)
+        }
+        ExpenseType::Group => expenses.group_expenses[idx].edit(prompter, &expenses.config),
+        ExpenseType::Recurring => expenses.recurring_expenses[idx].edit(prompter, &expenses.config),
+        ExpenseType::Todo => {
+            let to_edit = &expenses.debts_owed[idx];
+            println!("{}", to_edit.configured_display(&expenses.config));
+            let repayment = match Repayment::new(prompter, to_edit.outstanding()) {
+                Err(KakeboError::PromptCanceled) => return Ok(false),
+                repayment => repayment?,
+            };
+            expenses.repay_debt(idx, repayment);
+            Ok(true)
+        }
+        ExpenseType::Advance => {
+            let to_edit = &expenses.unpaid_advancements[idx];
+            println!("{}", to_edit.configured_display(&expenses.config));
+            let repayment = match Repayment::new(prompter, to_edit.outstanding()) {
+                Err(KakeboError::PromptCanceled) => return Ok(false),
+                repayment => repayment?,
+            };
+            expenses.repay_advancement(idx, repayment);
+            Ok(true)
+        }
     }
-    Ok(deletion_confirmed)
 }
 
 struct DisplayPath {
@@ -366,114 +388,36 @@ struct PathCache {
     data: Vec<String>,
 }
 
-fn run() -> Result<(), KakeboError> {
-    let args = Args::parse();
-
-    let search_dir = dirs::home_dir().expect("Resolve home directory");
-
-    let cache_path = search_dir.join(".kakebo-cache");
-    let cached_possible_paths: Option<Vec<_>> = if cache_path.exists() {
-        let cache = std::fs::read_to_string(&cache_path)?;
-        let possible_paths: PathCache = toml::from_str(&cache)?;
-        Some(
-            possible_paths
-                .data
-                .into_iter()
-                .map(|s| DisplayPath {
-                    inner: Path::new(&s).to_owned(),
-                })
-                .collect(),
-        )
-    } else {
-        None
-    };
-
-    let cache_is_valid = if let Some(cached_paths) = &cached_possible_paths {
-        cached_paths
-            .iter()
-            .all(|display_path| display_path.inner.exists())
-    } else {
-        false
-    };
-
-    let mut possible_paths = if cache_is_valid {
-        cached_possible_paths.unwrap()
-    } else {
-        let mut possible_paths = Vec::new();
-        for entry in WalkDir::new(search_dir)
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let filename = entry.file_name().to_string_lossy();
-
-            if filename.ends_with(".kakebo") {
-                possible_paths.push(DisplayPath {
-                    inner: entry.path().to_owned(),
-                });
-            }
-        }
-        let cache: Vec<String> = possible_paths
-            .iter()
-            .map(|display_path| display_path.inner.to_string_lossy().into_owned())
-            .collect();
-        let cache = PathCache { data: cache };
-        let cache_str = toml::to_string_pretty(&cache)?;
-        std::fs::write(&cache_path, cache_str)?;
-        possible_paths
-    };
-
-    let path = match possible_paths.len() {
-        0 => {
-            return Err(KakeboError::InvalidArgument(
-                "Could not find any database".to_string(),
-            ))
-        }
-        1 => possible_paths.pop().unwrap(),
-        _ => Select::new(
-            "Which Kakebo database do you want to access?",
-            possible_paths,
-        )
-        .prompt()?,
-    };
-    let path = Path::new(&path.inner);
-
-    // NOTE: this is a way to update the file format, I leave this as reference
-    // transform::<OldExpenses, Expenses>(path)?;
-    // return Ok(());
-
-    let mut expenses: Expenses = parse_file(path)?;
+/// runs a command that is not undo or redo, returns whether it changed anything
+fn execute(
+    prompter: &mut dyn Prompter,
+    expenses: &mut Expenses,
+    command: Command,
+    debug: bool,
+    today: NaiveDate,
+) -> Result<bool, KakeboError> {
     let mut environment = Environment {
         people: expenses.all_people().collect(),
+        tags: expenses.all_tags().collect(),
+        categories: expenses.categories.names().to_vec(),
     };
 
-    if args.debug {
-        println!(
-            "=== Expenses Before ===\n{:?}\n=======================",
-            expenses
-        );
-    }
-
-    let changes_made = match args.command {
+    let changes_made = match command {
         Command::Status { expense_type } => {
             match expense_type {
                 None => {
                     println!("===== STATUS =====");
                     println!("User: {}", expenses.config.user_name);
                     println!("Currency: {}", expenses.config.currency);
-                    expenses.print_status();
-                    if args.debug {
+                    cli::print_status(expenses);
+                    if debug {
                         println!("{:?}", expenses);
                     }
                 }
-                Some(ExpenseType::Single) => status(&expenses.single_expenses, &expenses.config)?,
-                Some(ExpenseType::Group) => status(&expenses.group_expenses, &expenses.config)?,
-                Some(ExpenseType::Recurring) => {
-                    status(&expenses.recurring_expenses, &expenses.config)?
-                }
-                Some(ExpenseType::Todo) => status(&expenses.debts_owed, &expenses.config)?,
-                Some(ExpenseType::Advance) => {
-                    status(&expenses.unpaid_advancements, &expenses.config)?
+                Some(expense_type) => {
+                    if let Some(idx) = select_record(prompter, expenses, expense_type, "view")? {
+                        show_record(expenses, expense_type, idx);
+                    }
                 }
             }
             false
@@ -485,47 +429,7 @@ fn run() -> Result<(), KakeboError> {
                     person
                 )));
             }
-            let debts_from_group_expenses =
-                expenses.group_expenses.iter().flat_map(|group_expense| {
-                    group_expense.parts().filter(|part| {
-                        part.person == person && part.to_pay != part.paid.unwrap_or(Decimal::ZERO)
-                    })
-                });
-
-            let mut total_owed = Decimal::ZERO;
-            let mut total_overflow = Decimal::ZERO;
-
-            for part in debts_from_group_expenses {
-                println!("  {}", part);
-                let paid = part.paid.unwrap_or(Decimal::ZERO);
-                match paid.cmp(&part.to_pay) {
-                    Ordering::Less => {
-                        let owed = part.to_pay - paid;
-                        total_owed += owed;
-                    }
-                    Ordering::Greater => {
-                        let overpaid = paid - part.to_pay;
-                        total_overflow += overpaid
-                    }
-                    Ordering::Equal => {}
-                }
-            }
-            // TODO: debts owed and advancements
-            let overflow = expenses.overflows.get(&person).unwrap_or(&Decimal::ZERO);
-            total_overflow += overflow;
-            println!("  {} {:8.2} overflow", person, overflow);
-            println!("             they owe   you owe          balance");
-            let balance = total_owed - total_overflow;
-            let balance_color = if Decimal::abs(&balance) >= Decimal::from(5) {
-                ANSI_RED
-            } else {
-                ANSI_GREEN
-            };
-            println!(
-                "  {:10} {:8.2}, {:8.2}, TOTAL: {balance_color}{:+8.2}{ANSI_STOP}",
-                person, total_owed, total_overflow, balance
-            );
-
+            cli::print_ledger(&expenses.ledger(&person), false);
             false
         }
         Command::List { person } => {
@@ -535,286 +439,429 @@ fn run() -> Result<(), KakeboError> {
                     person
                 )));
             }
-
-            let all_expenses = expenses.group_expenses.iter().flat_map(|group_expense| {
-                group_expense.parts().filter(|part| part.person == person)
-            });
-
-            let mut total_owed = Decimal::ZERO;
-            let mut total_overflow = Decimal::ZERO;
-
-            for part in all_expenses {
-                println!("  {}", part);
-                let paid = part.paid.unwrap_or(Decimal::ZERO);
-                match paid.cmp(&part.to_pay) {
-                    Ordering::Less => {
-                        let owed = part.to_pay - paid;
-                        total_owed += owed;
-                    }
-                    Ordering::Greater => {
-                        let overpaid = paid - part.to_pay;
-                        total_overflow += overpaid
-                    }
-                    Ordering::Equal => {}
+            cli::print_ledger(&expenses.ledger(&person), true);
+            false
+        }
+        Command::Qr { person, output } => {
+            if expenses.all_people().all(|p| p != person) {
+                return Err(KakeboError::InvalidArgument(format!(
+                    "{} is not a known person",
+                    person
+                )));
+            }
+            let ledger = expenses.ledger(&person);
+            let code = qr::code(&ledger, &expenses.config)?;
+            match output {
+                Some(output) => {
+                    qr::write(&code, &output)?;
+                    println!("Wrote QR code to {}", output.display());
+                }
+                None => {
+                    println!("{}", qr::to_terminal(&code));
+                    println!(
+                        "{} owes you {:.2}{}",
+                        ledger.person,
+                        ledger.balance(),
+                        expenses.config.currency
+                    );
                 }
             }
-            // TODO: debts owed and advancements
-            let overflow = expenses.overflows.get(&person).unwrap_or(&Decimal::ZERO);
-            total_overflow += overflow;
-            println!("  {} {:8.2} overflow", person, overflow);
-            println!("             they owe   you owe          balance");
-            let balance = total_owed - total_overflow;
-            let balance_color = if Decimal::abs(&balance) >= Decimal::from(5) {
-                ANSI_RED
-            } else {
-                ANSI_GREEN
-            };
-            println!(
-                "  {:10} {:8.2}, {:8.2}, TOTAL: {balance_color}{:+8.2}{ANSI_STOP}",
-                person, total_owed, total_overflow, balance
-            );
-
             false
         }
-        Command::Delete { expense_type } => match expense_type {
-            ExpenseType::Single => delete(&mut expenses.single_expenses, &expenses.config)?,
-            ExpenseType::Group => delete(&mut expenses.group_expenses, &expenses.config)?,
-            ExpenseType::Recurring => delete(&mut expenses.recurring_expenses, &expenses.config)?,
-            ExpenseType::Todo => delete(&mut expenses.debts_owed, &expenses.config)?,
-            ExpenseType::Advance => delete(&mut expenses.unpaid_advancements, &expenses.config)?,
-        },
+        Command::Statement { person, format } => {
+            if expenses.all_people().all(|p| p != person) {
+                return Err(KakeboError::InvalidArgument(format!(
+                    "{} is not a known person",
+                    person
+                )));
+            }
+            let ledger = expenses.ledger(&person);
+            print!("{}", ledger.statement(format, &expenses.config));
+            false
+        }
+        Command::Delete { expense_type, id } => {
+            let target = target_record(prompter, expenses, id, expense_type, "delete")?;
+            match target {
+                Some((expense_type, idx)) => delete_record(prompter, expenses, expense_type, idx)?,
+                None => false,
+            }
+        }
+        Command::Show { id } => {
+            let (expense_type, idx) = expenses.locate_or_err(id)?;
+            show_record(expenses, expense_type, idx);
+            false
+        }
         Command::Add { expense_type } => {
             match expense_type {
                 ExpenseType::Single => {
-                    let single = SingleExpense::new(&expenses.config)?;
-                    if args.debug {
+                    let mut single = SingleExpense::new(prompter, &environment, &expenses.config)?;
+                    if debug {
                         println!("{:?}", single);
                     }
+                    expenses.categories.register(&single.info.category);
+                    single.set_id(expenses.new_id());
                     expenses.single_expenses.push(single);
                 }
                 ExpenseType::Group => {
-                    let group = GroupExpense::new(&environment, &expenses.config)?;
-                    if args.debug {
+                    let mut group = GroupExpense::new(prompter, &environment, &expenses.config)?;
+                    if debug {
                         println!("{:?}", group);
                     }
                     environment
                         .people
                         .extend(group.people.iter().map(String::clone));
+                    expenses.categories.register(&group.info.category);
+                    group.set_id(expenses.new_id());
                     expenses.group_expenses.push(group);
                 }
                 ExpenseType::Recurring => {
-                    let recurring = RecurringExpense::new(&expenses.config)?;
-                    if args.debug {
+                    let mut recurring =
+                        RecurringExpense::new(prompter, &environment, &expenses.config)?;
+                    if debug {
                         println!("{:?}", recurring);
                     }
+                    expenses.categories.register(&recurring.info.category);
+                    recurring.set_id(expenses.new_id());
                     expenses.recurring_expenses.push(recurring);
+                    expenses.materialize_occurrences(today);
                 }
                 ExpenseType::Todo => {
-                    let debt = Debt::new(&environment, &expenses.config)?;
-                    if args.debug {
+                    let mut debt = Debt::new(prompter, &environment, &expenses.config)?;
+                    if debug {
                         println!("{:?}", debt);
                     }
                     environment.people.insert(debt.person.clone());
+                    expenses.categories.register(&debt.expense.info.category);
+                    debt.set_id(expenses.new_id());
                     expenses.debts_owed.push(debt);
                 }
                 ExpenseType::Advance => {
-                    let advancement = Advancement::new(&environment, &expenses.config)?;
-                    if args.debug {
+                    let mut advancement =
+                        Advancement::new(prompter, &environment, &expenses.config)?;
+                    if debug {
                         println!("{:?}", advancement);
                     }
                     environment.people.insert(advancement.person.clone());
+                    advancement.set_id(expenses.new_id());
                     expenses.unpaid_advancements.push(advancement);
                 }
             }
             true
         }
-        Command::Edit { expense_type } => {
+        Command::Edit { expense_type, id } => {
             println!("Editing...");
-            match expense_type {
-                ExpenseType::Single => todo!("Edit single expenses"),
-                ExpenseType::Group => {
-                    if expenses.group_expenses.is_empty() {
-                        println!("No {} to edit.", GroupExpense::plural_name());
-                        false
-                    } else {
-                        let options: Vec<_> = expenses.group_expenses.iter_mut().rev().collect();
-                        let to_edit =
-                            Select::new("Which group expense do you want to edit?", options)
-                                .prompt()?;
-                        to_edit.edit(&expenses.config)?
-                    }
-                }
-                ExpenseType::Recurring => todo!("Edit recurring expenses"),
-                ExpenseType::Todo => {
-                    if expenses.debts_owed.is_empty() {
-                        println!("No {} to edit.", Debt::plural_name());
-                        false
-                    } else {
-                        let options: Vec<_> = expenses.debts_owed.iter().rev().collect();
-                        let to_edit =
-                            Select::new("Which debt do you want to edit?", options).prompt()?;
-                        println!("{}", to_edit);
-                        let payed_up = Confirm::new(&format!(
-                            "Have you paid {} back the {}{}?",
-                            to_edit.person, to_edit.expense.amount, expenses.config.currency
-                        ))
-                        .prompt()?;
-                        if payed_up {
-                            let index = expenses
-                                .debts_owed
-                                .iter()
-                                .position(|debt| debt == to_edit)
-                                .expect("The debt we edit must exist");
-                            let debt_paid = expenses.debts_owed.remove(index);
-                            expenses.single_expenses.push(debt_paid.expense);
-                        }
-                        payed_up
-                    }
-                }
-                ExpenseType::Advance => {
-                    if expenses.unpaid_advancements.is_empty() {
-                        println!("No {} to edit.", Advancement::plural_name());
-                        false
-                    } else {
-                        let options: Vec<_> = expenses.unpaid_advancements.iter().rev().collect();
-                        let to_edit =
-                            Select::new("Which debt do you want to edit?", options).prompt()?;
-                        println!("{}", to_edit);
-                        let payed_up = Confirm::new(&format!(
-                            "Has {} paid you back the {}{}?",
-                            to_edit.person, to_edit.amount, expenses.config.currency
-                        ))
-                        .prompt()?;
-                        if payed_up {
-                            let index = expenses
-                                .unpaid_advancements
-                                .iter()
-                                .position(|advancement| advancement == to_edit)
-                                .expect("The advancement we edit must exist");
-                            expenses.unpaid_advancements.remove(index);
-                        }
-                        payed_up
-                    }
+            let target = target_record(prompter, expenses, id, expense_type, "edit")?;
+            match target {
+                Some((expense_type, idx)) => {
+                    edit_record(prompter, expenses, &environment, expense_type, idx)?
                 }
+                None => false,
             }
         }
-        Command::Receive { value, from } => {
-            let source_person = if let Some(src) = from {
-                if environment.people.contains(&src) {
-                    src
-                } else {
-                    return Err(KakeboError::InvalidArgument(format!(
-                        "Cannot receive {} from {}, {} does not exist",
-                        value, src, src
-                    )));
-                }
+        Command::Receive {
+            value,
+            from,
+            strategy,
+        } => {
+            let source_person = counterparty(
+                prompter,
+                from,
+                value,
+                ("receive", "from"),
+                "Who did you receive this money from?",
+                &environment,
+            )?;
+            let settlement = expenses.settle(
+                prompter,
+                Direction::Received,
+                &source_person,
+                value,
+                strategy,
+            )?;
+            cli::print_preview(&settlement.allocations, &expenses.config);
+            println!(
+                "  {:8.2}{} -> overflow",
+                settlement.overflow, expenses.config.currency
+            );
+            if settlement.changes_anything() && prompter.confirm("Apply this allocation?")? {
+                expenses.apply_settlement(settlement);
+                true
             } else {
-                let options = environment.people.iter().map(|s| s.to_string()).collect();
-                Select::new("Who did you receive this money from?", options).prompt()?
-            };
-            if value < Decimal::ZERO {
-                return Err(KakeboError::InvalidArgument(format!(
-                    "Cannot receive {} from {}, {} is negative",
-                    value, source_person, value
-                )));
+                false
             }
-            let mut this_value = value;
-            let mut overflow = *expenses
-                .overflows
-                .get(&source_person)
-                .unwrap_or(&Decimal::ZERO);
-            let mut any_change = false;
-            while this_value + overflow > Decimal::ZERO {
-                // TODO: implement non-group expense behaviour
-                let options: Vec<_> = expenses
-                    .group_expenses
-                    .iter_mut()
-                    .flat_map(|group_expense| {
-                        group_expense.parts().filter(|part| {
-                            part.person == source_person
-                                && part.to_pay > part.paid.unwrap_or(Decimal::ZERO)
-                        })
-                    })
-                    .collect();
+        }
+        Command::Pay {
+            value,
+            to,
+            strategy,
+        } => {
+            let target_person = counterparty(
+                prompter,
+                to,
+                value,
+                ("pay", "to"),
+                "Who did you pay this money to?",
+                &environment,
+            )?;
+            let settlement =
+                expenses.settle(prompter, Direction::Paid, &target_person, value, strategy)?;
+            cli::print_preview(&settlement.allocations, &expenses.config);
+            // a negative overflow is a credit the person has with the user
+            if settlement.overflow < Decimal::ZERO {
                 println!(
-                    "There is {} + {} = {} unassigned",
-                    this_value,
-                    overflow,
-                    this_value + overflow
+                    "  {:8.2}{} -> credit",
+                    -settlement.overflow, expenses.config.currency
                 );
-                if options.is_empty() {
-                    break;
-                }
-                let pay_off_option =
-                    Select::new("Which group expense did this pay?", options).prompt();
-                if let Err(inquire::InquireError::OperationCanceled) = pay_off_option {
-                    break;
+            } else {
+                println!(
+                    "  {:8.2}{} still owed",
+                    settlement.overflow, expenses.config.currency
+                );
+            }
+            if settlement.changes_anything() && prompter.confirm("Apply this allocation?")? {
+                expenses.apply_settlement(settlement);
+                true
+            } else {
+                false
+            }
+        }
+        Command::Sanitize => {
+            expenses.sanitize();
+            true
+        }
+        Command::Search { filter, by } => {
+            let entries = search::search(expenses, &filter);
+            cli::print_entries(&entries, by, &expenses.config);
+            false
+        }
+        Command::Config { action } => match action {
+            ConfigAction::Show => {
+                let config = &expenses.config;
+                println!("  user name:            {}", config.user_name);
+                println!("  currency:             {}", config.currency);
+                println!("  decimal separator:    {}", config.decimal_sep);
+                println!(
+                    "  payment instructions: {}",
+                    config.payment_instructions.as_deref().unwrap_or("none")
+                );
+                println!(
+                    "  IBAN:                 {}",
+                    config.iban.as_deref().unwrap_or("none")
+                );
+                println!(
+                    "  BIC:                  {}",
+                    config.bic.as_deref().unwrap_or("none")
+                );
+                false
+            }
+            ConfigAction::Iban { iban } => {
+                expenses.config.iban = iban.as_deref().map(qr::normalize_iban).transpose()?;
+                true
+            }
+            ConfigAction::Bic { bic } => {
+                expenses.config.bic = bic.map(|bic| bic.trim().to_uppercase());
+                true
+            }
+            ConfigAction::PaymentInstructions { instructions } => {
+                expenses.config.payment_instructions = instructions;
+                true
+            }
+        },
+        Command::Category { action } => match action {
+            CategoryAction::List => {
+                for name in expenses.categories.names() {
+                    println!("  {}", name);
                 }
-                let part = pay_off_option?;
-                let mut possible_targets = expenses
-                    .group_expenses
-                    .iter_mut()
-                    .filter(|group_expense| group_expense.info == part.info);
-                let group_expense: &mut GroupExpense = possible_targets
-                    .next()
-                    .expect("We previously checked for this existence");
-                assert!(
-                    possible_targets.next().is_none(),
-                    "Our implementation is invalid if ExpenseInfo is not unique"
+                false
+            }
+            CategoryAction::Add { name } => {
+                expenses.categories.add(&name)?;
+                true
+            }
+            CategoryAction::Rename { old, new } => {
+                let changed = expenses.rename_category(&old, &new)?;
+                println!("Renamed {} to {} ({} expenses updated)", old, new, changed);
+                true
+            }
+            CategoryAction::Merge { from, into } => {
+                let changed = expenses.merge_category(&from, &into)?;
+                println!(
+                    "Merged {} into {} ({} expenses updated)",
+                    from, into, changed
                 );
+                true
+            }
+            CategoryAction::Remove { name } => {
+                expenses.remove_category(&name)?;
+                true
+            }
+        },
+        Command::Upcoming { days } => {
+            cli::print_upcoming(&upcoming::forecast(expenses, today, days), &expenses.config);
+            false
+        }
+        Command::Report { options } => {
+            let report = report::report(expenses, &options);
+            cli::print_report(&report, options.by, &expenses.config);
+            false
+        }
+        Command::Undo | Command::Redo => {
+            unreachable!("Undo and redo restore operations instead of being one")
+        }
+        Command::Log { count } => {
+            cli::print_log(&expenses.journal, count);
+            false
+        }
+        Command::History { record } => {
+            cli::print_history(&expenses.history, record);
+            false
+        }
+    };
+    Ok(changes_made)
+}
 
-                let mut still_to_pay = part.to_pay - part.paid.unwrap_or(Decimal::ZERO);
-                let mut paying = Decimal::ZERO;
+fn run() -> Result<(), KakeboError> {
+    let args = Args::parse();
+    let mut prompter: Box<dyn Prompter> = match &args.answers {
+        Some(answers) => {
+            Box::new(ScriptedPrompter::from_file(answers)?.with_echo(std::io::stdout()))
+        }
+        None => Box::new(InquirePrompter),
+    };
+    let prompter = prompter.as_mut();
 
-                let non_overflow_part = this_value.min(still_to_pay);
-                this_value -= non_overflow_part;
-                paying += non_overflow_part;
-                still_to_pay -= non_overflow_part;
+    let search_dir = dirs::home_dir().expect("Resolve home directory");
 
-                if !still_to_pay.is_zero() {
-                    let overflow_part = overflow.min(still_to_pay);
-                    overflow -= overflow_part;
-                    paying += overflow_part;
-                    still_to_pay -= overflow_part;
-                }
+    let cache_path = search_dir.join(".kakebo-cache");
+    let cached_possible_paths: Option<Vec<_>> = if cache_path.exists() {
+        let cache = std::fs::read_to_string(&cache_path)?;
+        let possible_paths: PathCache = toml::from_str(&cache)?;
+        Some(
+            possible_paths
+                .data
+                .into_iter()
+                .map(|s| DisplayPath {
+                    inner: Path::new(&s).to_owned(),
+                })
+                .collect(),
+        )
+    } else {
+        None
+    };
 
-                let now_paid = part.paid.unwrap_or(Decimal::ZERO) + paying;
-                group_expense.paid_amounts[part.index] = Some(now_paid);
-                any_change = true;
-            }
-            let new_overflow = this_value + overflow;
-            if new_overflow
-                != *expenses
-                    .overflows
-                    .get(&source_person)
-                    .unwrap_or(&Decimal::ZERO)
-            {
-                expenses.overflows.insert(source_person, new_overflow);
-                any_change = true;
+    let cache_is_valid = if let Some(cached_paths) = &cached_possible_paths {
+        cached_paths
+            .iter()
+            .all(|display_path| display_path.inner.exists())
+    } else {
+        false
+    };
+
+    let mut possible_paths = if cache_is_valid {
+        cached_possible_paths.unwrap()
+    } else {
+        let mut possible_paths = Vec::new();
+        for entry in WalkDir::new(search_dir)
+            .follow_links(true)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let filename = entry.file_name().to_string_lossy();
+
+            if filename.ends_with(".kakebo") {
+                possible_paths.push(DisplayPath {
+                    inner: entry.path().to_owned(),
+                });
             }
-            any_change
         }
-        Command::Sanitize => {
-            for group_expense in expenses.group_expenses.iter_mut() {
-                for (i, to_pay) in group_expense.true_amounts().into_iter().enumerate() {
-                    let person = group_expense.people[i].clone();
-                    let paid = group_expense.paid_amounts[i].unwrap_or(Decimal::ZERO);
-                    if paid > to_pay {
-                        group_expense.paid_amounts[i] = Some(to_pay);
-                        let overflow = paid - to_pay;
-                        expenses
-                            .overflows
-                            .entry(person)
-                            .and_modify(|mut v| v += overflow)
-                            .or_insert(overflow);
-                    }
-                }
-            }
-            true
+        let cache: Vec<String> = possible_paths
+            .iter()
+            .map(|display_path| display_path.inner.to_string_lossy().into_owned())
+            .collect();
+        let cache = PathCache { data: cache };
+        let cache_str = toml::to_string_pretty(&cache)?;
+        std::fs::write(&cache_path, cache_str)?;
+        possible_paths
+    };
+
+    let path = match possible_paths.len() {
+        0 => {
+            return Err(KakeboError::InvalidArgument(
+                "Could not find any database".to_string(),
+            ))
         }
+        1 => possible_paths.pop().unwrap(),
+        _ => choose(
+            prompter,
+            "Which Kakebo database do you want to access?",
+            possible_paths,
+        )?,
+    };
+    let path = Path::new(&path.inner);
+
+    // NOTE: this is a way to update the file format, I leave this as reference
+    // storage::transform::<OldExpenses, Expenses>(path, &passphrase("Enter password:")?)?;
+    // return Ok(());
+
+    let mut expenses: Expenses = if path.exists() {
+        let expenses = storage::parse_file(path, &passphrase("Enter decryption password:")?)?;
+        println!("Expenses parsed from {}", path.display());
+        expenses
+    } else {
+        println!("File {} does not exist.", path.display());
+        Expenses::default()
+    };
+    expenses.register_used_categories();
+    expenses.assign_missing_ids();
+
+    let is_journaled = !matches!(
+        args.command,
+        Command::Undo | Command::Redo | Command::Log { .. }
+    );
+
+    // due occurrences are recorded as an operation of their own, so they can be undone and
+    // their group expenses start their history when they are created, undo and redo leave
+    // them for the next command so they act on the operation the user expects
+    let today = Local::now().date_naive();
+    let materialized = is_journaled
+        && expenses.apply("(record due occurrences)", |expenses| {
+            Ok(expenses.materialize_occurrences(today))
+        })?;
+    if args.debug {
+        println!(
+            "=== Expenses Before ===\n{:?}\n=======================",
+            expenses
+        );
+    }
+
+    let command_line = std::env::args().skip(1).collect::<Vec<_>>().join(" ");
+    let changes_made = match args.command {
+        Command::Undo => match expenses.undo()? {
+            Some(operation) => {
+                println!("Undid: {}", operation);
+                true
+            }
+            None => {
+                println!("Nothing to undo.");
+                false
+            }
+        },
+        Command::Redo => match expenses.redo()? {
+            Some(operation) => {
+                println!("Redid: {}", operation);
+                true
+            }
+            None => {
+                println!("Nothing to redo.");
+                false
+            }
+        },
+        command => expenses.apply(&command_line, |expenses| {
+            execute(prompter, expenses, command, args.debug, today)
+        })?,
     };
 
-    if !changes_made {
+    if !changes_made && !materialized {
         return Ok(());
     }
 
@@ -825,7 +872,7 @@ fn run() -> Result<(), KakeboError> {
         );
     }
 
-    write_file(path, &expenses)
+    storage::write_file(path, &expenses, &passphrase("Enter encryption password:")?)
 }
 
 fn main() -> ExitCode {
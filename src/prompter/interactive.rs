@@ -0,0 +1,125 @@
+use chrono::{NaiveDate, Weekday};
+use inquire::{
+    autocompletion::Replacement, list_option::ListOption, validator::Validation, Autocomplete,
+    Confirm, CustomUserError, DateSelect, MultiSelect, Select, Text,
+};
+
+use crate::errors::KakeboError;
+
+use super::{Prompter, Question};
+
+/// asks the user in the terminal
+#[derive(Debug, Default)]
+pub struct InquirePrompter;
+
+/// completes the last word of the input with one of the suggestions
+#[derive(Clone)]
+struct WordCompleter {
+    words: Vec<String>,
+}
+
+impl WordCompleter {
+    fn split_last_word(input: &str) -> (&str, &str) {
+        match input.rfind([' ', ',']) {
+            Some(index) => input.split_at(index + 1),
+            None => ("", input),
+        }
+    }
+
+    /// compares words without a leading `#`, so tags match with and without it
+    fn normalize(word: &str) -> String {
+        word.trim_start_matches('#').to_lowercase()
+    }
+}
+
+impl Autocomplete for WordCompleter {
+    fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, CustomUserError> {
+        let (before, last_word) = Self::split_last_word(input);
+        let already_entered: Vec<_> = before
+            .split([' ', ','])
+            .filter(|word| !word.is_empty())
+            .map(Self::normalize)
+            .collect();
+        let prefix = Self::normalize(last_word);
+        Ok(self
+            .words
+            .iter()
+            .filter(|word| Self::normalize(word).starts_with(&prefix))
+            .filter(|word| !already_entered.contains(&Self::normalize(word)))
+            .cloned()
+            .collect())
+    }
+
+    fn get_completion(
+        &mut self,
+        input: &str,
+        highlighted_suggestion: Option<String>,
+    ) -> Result<Replacement, CustomUserError> {
+        Ok(highlighted_suggestion.map(|suggestion| {
+            let (before, _) = Self::split_last_word(input);
+            format!("{before}{suggestion} ")
+        }))
+    }
+}
+
+impl Prompter for InquirePrompter {
+    fn input(&mut self, question: &Question<'_>) -> Result<String, KakeboError> {
+        let validator = question.validator.clone();
+        let mut text = Text::new(question.message).with_validator(move |input: &str| {
+            Ok(match validator(input) {
+                Ok(()) => Validation::Valid,
+                Err(reason) => Validation::Invalid(reason.into()),
+            })
+        });
+        if let Some(help) = question.help {
+            text = text.with_help_message(help);
+        }
+        if let Some(default) = &question.default {
+            text = text.with_default(default);
+        }
+        if !question.suggestions.is_empty() {
+            text = text.with_autocomplete(WordCompleter {
+                words: question.suggestions.clone(),
+            });
+        }
+        Ok(text.prompt()?)
+    }
+
+    fn select(&mut self, message: &str, options: &[String]) -> Result<usize, KakeboError> {
+        Ok(Select::new(message, options.to_vec()).raw_prompt()?.index)
+    }
+
+    fn multi_select(
+        &mut self,
+        message: &str,
+        options: &[String],
+        min: usize,
+    ) -> Result<Vec<usize>, KakeboError> {
+        let selected = MultiSelect::new(message, options.to_vec())
+            .with_validator(move |selected: &[ListOption<&String>]| {
+                if selected.len() < min {
+                    Ok(Validation::Invalid(
+                        format!("Select at least {}", min).into(),
+                    ))
+                } else {
+                    Ok(Validation::Valid)
+                }
+            })
+            .raw_prompt()?;
+        Ok(selected.into_iter().map(|option| option.index).collect())
+    }
+
+    fn confirm(&mut self, message: &str) -> Result<bool, KakeboError> {
+        Ok(Confirm::new(message).prompt()?)
+    }
+
+    fn date(&mut self, message: &str) -> Result<NaiveDate, KakeboError> {
+        Ok(DateSelect::new(message)
+            .with_week_start(Weekday::Mon)
+            .prompt()?)
+    }
+
+    fn message(&mut self, text: &str) {
+        println!("{}", text);
+    }
+}
@@ -0,0 +1,132 @@
+use std::{fmt::Display, rc::Rc};
+
+use chrono::NaiveDate;
+
+use crate::errors::KakeboError;
+
+#[cfg(feature = "interactive")]
+pub mod interactive;
+pub mod scripted;
+
+#[cfg(feature = "interactive")]
+pub use interactive::InquirePrompter;
+pub use scripted::ScriptedPrompter;
+
+type Validator = Rc<dyn Fn(&str) -> Result<(), String>>;
+
+/// a question answered with free text, the validator decides which answers are accepted
+pub struct Question<'a> {
+    pub message: &'a str,
+    pub help: Option<&'a str>,
+    /// the answer used if the user gives an empty one
+    pub default: Option<String>,
+    /// words that can complete the last word of the answer, e.g. the known tags
+    pub suggestions: Vec<String>,
+    validator: Validator,
+}
+
+impl<'a> Question<'a> {
+    pub fn new(message: &'a str) -> Self {
+        Self {
+            message,
+            help: None,
+            default: None,
+            suggestions: Vec::new(),
+            validator: Rc::new(|_| Ok(())),
+        }
+    }
+
+    pub fn with_help(mut self, help: &'a str) -> Self {
+        self.help = Some(help);
+        self
+    }
+
+    pub fn with_default(mut self, default: impl ToString) -> Self {
+        self.default = Some(default.to_string());
+        self
+    }
+
+    pub fn with_suggestions(mut self, suggestions: Vec<String>) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+
+    /// rejects answers for which the validator returns an error message
+    pub fn with_validator(
+        mut self,
+        validator: impl Fn(&str) -> Result<(), String> + 'static,
+    ) -> Self {
+        self.validator = Rc::new(validator);
+        self
+    }
+
+    /// the error message for an answer that is not accepted
+    pub fn validate(&self, answer: &str) -> Result<(), String> {
+        (self.validator)(answer)
+    }
+}
+
+/// the way the creation and editing flows ask the user for input
+///
+/// Canceling a prompt, e.g. by pressing ESC, is reported as [`KakeboError::PromptCanceled`]
+/// so the flows can treat it as "done" where that makes sense.
+pub trait Prompter {
+    /// free text accepted by the question's validator
+    fn input(&mut self, question: &Question<'_>) -> Result<String, KakeboError>;
+    /// the index of the chosen option
+    fn select(&mut self, message: &str, options: &[String]) -> Result<usize, KakeboError>;
+    /// the indices of the chosen options, at least `min` of them
+    fn multi_select(
+        &mut self,
+        message: &str,
+        options: &[String],
+        min: usize,
+    ) -> Result<Vec<usize>, KakeboError>;
+    fn confirm(&mut self, message: &str) -> Result<bool, KakeboError>;
+    fn date(&mut self, message: &str) -> Result<NaiveDate, KakeboError>;
+    /// shows information to the user, e.g. the record that is about to be saved
+    fn message(&mut self, text: &str);
+}
+
+/// asks the question until the answer can be parsed, the parser's error is shown to the user
+pub fn ask<T>(
+    prompter: &mut dyn Prompter,
+    question: Question<'_>,
+    parse: impl Fn(&str) -> Result<T, String> + Clone + 'static,
+) -> Result<T, KakeboError> {
+    let validate = parse.clone();
+    let question = question.with_validator(move |input| validate(input).map(|_| ()));
+    let answer = prompter.input(&question)?;
+    parse(&answer).map_err(KakeboError::InvalidArgument)
+}
+
+/// lets the user choose one of the options
+pub fn choose<T: Display>(
+    prompter: &mut dyn Prompter,
+    message: &str,
+    options: Vec<T>,
+) -> Result<T, KakeboError> {
+    let labels: Vec<_> = options.iter().map(T::to_string).collect();
+    let index = prompter.select(message, &labels)?;
+    options
+        .into_iter()
+        .nth(index)
+        .ok_or_else(|| KakeboError::InvalidArgument(format!("There is no option {}", index)))
+}
+
+/// lets the user choose at least `min` of the options
+pub fn choose_many<T: Display>(
+    prompter: &mut dyn Prompter,
+    message: &str,
+    options: Vec<T>,
+    min: usize,
+) -> Result<Vec<T>, KakeboError> {
+    let labels: Vec<_> = options.iter().map(T::to_string).collect();
+    let indices = prompter.multi_select(message, &labels, min)?;
+    Ok(options
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| indices.contains(i))
+        .map(|(_, option)| option)
+        .collect())
+}
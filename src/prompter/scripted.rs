@@ -0,0 +1,228 @@
+use std::{collections::VecDeque, io::Write, path::Path};
+
+use chrono::NaiveDate;
+
+use crate::errors::KakeboError;
+
+use super::{Prompter, Question};
+
+/// the answer that cancels a prompt, like pressing ESC
+pub const CANCEL: &str = "<esc>";
+
+/// answers the prompts from a prepared list, e.g. for tests and batch files
+///
+/// Every prompt takes the next answer:
+/// - text is used as it is, an empty answer takes the default if there is one
+/// - a selection is the text of an option or its position starting at 1
+/// - a multi-selection lists several of those separated by commas
+/// - a confirmation is `y`, `yes`, `n` or `no`
+/// - a date is written as YYYY-MM-DD
+///
+/// An answer of `<esc>` cancels the prompt.
+#[derive(Default)]
+pub struct ScriptedPrompter {
+    answers: VecDeque<String>,
+    messages: Vec<String>,
+    /// where messages are written to as well, if anywhere
+    echo: Option<Box<dyn Write>>,
+}
+
+impl std::fmt::Debug for ScriptedPrompter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScriptedPrompter")
+            .field("answers", &self.answers)
+            .field("messages", &self.messages)
+            .field("echo", &self.echo.is_some())
+            .finish()
+    }
+}
+
+impl ScriptedPrompter {
+    pub fn new<I, S>(answers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            answers: answers.into_iter().map(Into::into).collect(),
+            messages: Vec::new(),
+            echo: None,
+        }
+    }
+
+    /// writes the messages to `output` in addition to keeping them, e.g. to stdout
+    pub fn with_echo(mut self, output: impl Write + 'static) -> Self {
+        self.echo = Some(Box::new(output));
+        self
+    }
+
+    /// reads an answer file with one answer per line
+    pub fn from_file(path: &Path) -> Result<Self, KakeboError> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Self::new(content.lines()))
+    }
+
+    /// the messages shown so far
+    pub fn messages(&self) -> &[String] {
+        &self.messages
+    }
+
+    /// the answers that were not used
+    pub fn remaining(&self) -> impl Iterator<Item = &str> {
+        self.answers.iter().map(String::as_str)
+    }
+
+    fn next(&mut self, message: &str) -> Result<String, KakeboError> {
+        match self.answers.pop_front() {
+            Some(answer) if answer == CANCEL => Err(KakeboError::PromptCanceled),
+            Some(answer) => Ok(answer),
+            None => Err(KakeboError::InvalidArgument(format!(
+                "No answer left for \"{}\"",
+                message
+            ))),
+        }
+    }
+
+    fn invalid(message: &str, answer: &str, reason: &str) -> KakeboError {
+        KakeboError::InvalidArgument(format!(
+            "Invalid answer \"{}\" to \"{}\": {}",
+            answer, message, reason
+        ))
+    }
+
+    fn option_index(message: &str, options: &[String], answer: &str) -> Result<usize, KakeboError> {
+        let answer = answer.trim();
+        if let Some(index) = options.iter().position(|option| option == answer) {
+            return Ok(index);
+        }
+        match answer.parse::<usize>() {
+            Ok(position) if (1..=options.len()).contains(&position) => Ok(position - 1),
+            _ => Err(Self::invalid(
+                message,
+                answer,
+                &format!("expected one of {}", options.join(", ")),
+            )),
+        }
+    }
+}
+
+impl Prompter for ScriptedPrompter {
+    fn input(&mut self, question: &Question<'_>) -> Result<String, KakeboError> {
+        let mut answer = self.next(question.message)?;
+        if answer.is_empty() {
+            if let Some(default) = &question.default {
+                answer.clone_from(default);
+            }
+        }
+        question
+            .validate(&answer)
+            .map_err(|reason| Self::invalid(question.message, &answer, &reason))?;
+        Ok(answer)
+    }
+
+    fn select(&mut self, message: &str, options: &[String]) -> Result<usize, KakeboError> {
+        let answer = self.next(message)?;
+        Self::option_index(message, options, &answer)
+    }
+
+    fn multi_select(
+        &mut self,
+        message: &str,
+        options: &[String],
+        min: usize,
+    ) -> Result<Vec<usize>, KakeboError> {
+        let answer = self.next(message)?;
+        let indices = answer
+            .split(',')
+            .filter(|part| !part.trim().is_empty())
+            .map(|part| Self::option_index(message, options, part))
+            .collect::<Result<Vec<_>, _>>()?;
+        if indices.len() < min {
+            return Err(Self::invalid(
+                message,
+                &answer,
+                &format!("select at least {}", min),
+            ));
+        }
+        Ok(indices)
+    }
+
+    fn confirm(&mut self, message: &str) -> Result<bool, KakeboError> {
+        let answer = self.next(message)?;
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => Ok(true),
+            "n" | "no" => Ok(false),
+            _ => Err(Self::invalid(message, &answer, "expected yes or no")),
+        }
+    }
+
+    fn date(&mut self, message: &str) -> Result<NaiveDate, KakeboError> {
+        let answer = self.next(message)?;
+        answer
+            .trim()
+            .parse()
+            .map_err(|_| Self::invalid(message, &answer, "expected a date like 2026-01-31"))
+    }
+
+    fn message(&mut self, text: &str) {
+        // the messages are kept anyway, so echoing simply stops if the output fails
+        if let Some(output) = &mut self.echo {
+            if writeln!(output, "{}", text).is_err() {
+                self.echo = None;
+            }
+        }
+        self.messages.push(text.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io, rc::Rc};
+
+    use super::*;
+
+    /// an output that can still be read after it was handed to the prompter
+    #[derive(Clone, Default)]
+    struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct FailingOutput;
+
+    impl Write for FailingOutput {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn messages_are_echoed_to_the_output() {
+        let output = SharedOutput::default();
+        let mut prompter = ScriptedPrompter::new::<_, &str>([]).with_echo(output.clone());
+        prompter.message("first");
+        prompter.message("second");
+        assert_eq!(output.0.borrow().as_slice(), b"first\nsecond\n");
+        assert_eq!(prompter.messages(), ["first", "second"]);
+    }
+
+    #[test]
+    fn a_failing_output_stops_the_echo() {
+        let mut prompter = ScriptedPrompter::new::<_, &str>([]).with_echo(FailingOutput);
+        prompter.message("first");
+        assert!(prompter.echo.is_none());
+        prompter.message("second");
+        assert_eq!(prompter.messages(), ["first", "second"]);
+    }
+}
@@ -0,0 +1,197 @@
+use std::path::Path;
+
+use image::Luma;
+use qrcode::{render::svg, render::unicode, EcLevel, QrCode};
+use rust_decimal::Decimal;
+
+use crate::{errors::KakeboError, ledger::Ledger, KakeboConfig};
+
+/// the longest unstructured remittance information an EPC QR code may carry
+const MAX_REMITTANCE_LENGTH: usize = 140;
+/// the longest beneficiary name an EPC QR code may carry
+const MAX_NAME_LENGTH: usize = 70;
+
+/// removes spaces from an IBAN and checks its length and check digits
+pub fn normalize_iban(input: &str) -> Result<String, KakeboError> {
+    let iban: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let invalid = || KakeboError::InvalidArgument(format!("{} is not a valid IBAN", input));
+    if !(15..=34).contains(&iban.len()) || !iban.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(invalid());
+    }
+    // ISO 13616: move the first four characters to the end, replace letters by numbers, mod 97
+    let remainder = iban[4..]
+        .chars()
+        .chain(iban[..4].chars())
+        .fold(0u32, |remainder, c| {
+            let value = c
+                .to_digit(36)
+                .expect("IBANs only contain ASCII letters and digits");
+            if value < 10 {
+                (remainder * 10 + value) % 97
+            } else {
+                (remainder * 100 + value) % 97
+            }
+        });
+    if remainder != 1 {
+        return Err(invalid());
+    }
+    Ok(iban)
+}
+
+/// the EPC069-12 payload asking the person to pay the user what they owe
+fn epc_payload(ledger: &Ledger, config: &KakeboConfig) -> Result<String, KakeboError> {
+    let iban = config.iban.as_ref().ok_or_else(|| {
+        KakeboError::InvalidArgument(
+            "No IBAN configured, set one with `kakebo config iban <IBAN>`".to_string(),
+        )
+    })?;
+    if config.currency != '€' {
+        return Err(KakeboError::InvalidArgument(
+            "EPC QR codes only support payments in euro".to_string(),
+        ));
+    }
+    let amount = ledger.balance();
+    if amount <= Decimal::ZERO {
+        return Err(KakeboError::InvalidArgument(format!(
+            "{} does not owe you anything",
+            ledger.person
+        )));
+    }
+
+    let references: Vec<_> = ledger
+        .items
+        .iter()
+        .filter(|item| item.they_owe > Decimal::ZERO)
+        .map(|item| match item.id {
+            Some(id) => format!("[{}] {}", id, item.title),
+            None => item.title.clone(),
+        })
+        .collect();
+    let remittance: String = references
+        .join(", ")
+        .chars()
+        .take(MAX_REMITTANCE_LENGTH)
+        .collect();
+    let name: String = config.user_name.chars().take(MAX_NAME_LENGTH).collect();
+
+    let lines = [
+        "BCD",
+        "002",
+        "1",
+        "SCT",
+        config.bic.as_deref().unwrap_or(""),
+        &name,
+        iban,
+        &format!("EUR{:.2}", amount.round_dp(2)),
+        "",
+        "",
+        &remittance,
+    ];
+    Ok(lines.join("\n"))
+}
+
+/// the QR code asking the person to pay the user what they owe
+pub fn code(ledger: &Ledger, config: &KakeboConfig) -> Result<QrCode, KakeboError> {
+    let payload = epc_payload(ledger, config)?;
+    Ok(QrCode::with_error_correction_level(payload, EcLevel::M)?)
+}
+
+/// the QR code drawn with block characters, light on dark so that it scans in a terminal
+pub fn to_terminal(code: &QrCode) -> String {
+    code.render::<unicode::Dense1x2>()
+        .dark_color(unicode::Dense1x2::Light)
+        .light_color(unicode::Dense1x2::Dark)
+        .build()
+}
+
+/// writes the QR code to a PNG or SVG file, depending on the extension of `output`
+pub fn write(code: &QrCode, output: &Path) -> Result<(), KakeboError> {
+    match output.extension().and_then(|extension| extension.to_str()) {
+        Some("png") => code.render::<Luma<u8>>().build().save(output)?,
+        Some("svg") => std::fs::write(output, code.render::<svg::Color>().build())?,
+        _ => {
+            return Err(KakeboError::InvalidArgument(format!(
+                "Cannot write {}, QR codes can only be written as .png or .svg",
+                output.display()
+            )))
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{add_advancement, add_debt};
+    use crate::Expenses;
+
+    use super::*;
+
+    fn expenses() -> Expenses {
+        let mut expenses = Expenses::default();
+        expenses.config.iban = Some("DE89370400440532013000".to_string());
+        expenses
+    }
+
+    #[test]
+    fn ibans_are_checked_with_mod_97() {
+        assert_eq!(
+            normalize_iban("de89 3704 0044 0532 0130 00").unwrap(),
+            "DE89370400440532013000"
+        );
+        assert!(normalize_iban("DE88 3704 0044 0532 0130 00").is_err());
+        assert!(normalize_iban("DE89 3704").is_err());
+        assert!(normalize_iban("DE89-3704-0044-0532-0130-00").is_err());
+    }
+
+    #[test]
+    fn the_payload_follows_epc069_12() {
+        let mut expenses = expenses();
+        expenses.config.bic = Some("COBADEFFXXX".to_string());
+        let id = add_advancement(&mut expenses, "2026-03-01", "Anna", "12.5");
+        add_debt(&mut expenses, "2026-03-02", "Anna", "0.25");
+        let payload = epc_payload(&expenses.ledger("Anna"), &expenses.config).unwrap();
+        assert_eq!(
+            payload,
+            format!(
+                "BCD\n002\n1\nSCT\nCOBADEFFXXX\nFriedrich\nDE89370400440532013000\n\
+                 EUR12.25\n\n\n[{}] Advancement",
+                id
+            )
+        );
+    }
+
+    #[test]
+    fn the_amount_has_two_decimals() {
+        let mut expenses = expenses();
+        add_advancement(&mut expenses, "2026-03-01", "Anna", "7");
+        let payload = epc_payload(&expenses.ledger("Anna"), &expenses.config).unwrap();
+        assert_eq!(payload.lines().nth(7), Some("EUR7.00"));
+    }
+
+    #[test]
+    fn the_remittance_is_cut_to_140_characters() {
+        let mut expenses = expenses();
+        for day in 1..=20 {
+            add_advancement(&mut expenses, &format!("2026-03-{:02}", day), "Anna", "1");
+        }
+        let payload = epc_payload(&expenses.ledger("Anna"), &expenses.config).unwrap();
+        let remittance = payload.lines().last().unwrap();
+        assert_eq!(remittance.chars().count(), MAX_REMITTANCE_LENGTH);
+        assert!(remittance.starts_with("[1] Advancement, [2] Advancement, "));
+    }
+
+    #[test]
+    fn a_payment_needs_an_iban_and_something_owed() {
+        let mut expenses = expenses();
+        add_debt(&mut expenses, "2026-03-01", "Anna", "5");
+        assert!(epc_payload(&expenses.ledger("Anna"), &expenses.config).is_err());
+
+        add_advancement(&mut expenses, "2026-03-02", "Anna", "10");
+        expenses.config.iban = None;
+        assert!(epc_payload(&expenses.ledger("Anna"), &expenses.config).is_err());
+    }
+}
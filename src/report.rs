@@ -0,0 +1,242 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{Local, NaiveDate};
+use chronoutil::RelativeDuration;
+use clap::{Args, ValueEnum};
+use rust_decimal::Decimal;
+
+use crate::{expenses::ExpenseInfo, Expenses};
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Grouping {
+    #[default]
+    Category,
+    Tag,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct ReportOptions {
+    /// first day of the report (defaults to one month ago)
+    #[arg(long)]
+    pub from: Option<NaiveDate>,
+    /// last day of the report (defaults to today)
+    #[arg(long)]
+    pub to: Option<NaiveDate>,
+    /// how to group the expenses
+    #[arg(long, value_enum, default_value_t)]
+    pub by: Grouping,
+    /// only include expenses with this tag
+    #[arg(long)]
+    pub tag: Option<String>,
+    /// how many levels of subcategories to show (defaults to all levels)
+    #[arg(long)]
+    pub depth: Option<usize>,
+}
+
+/// the part of an expense the user paid for themselves within the report interval
+struct ReportItem<'a> {
+    info: &'a ExpenseInfo,
+    amount: Decimal,
+}
+
+fn items_in_interval(expenses: &Expenses, start: NaiveDate, end: NaiveDate) -> Vec<ReportItem<'_>> {
+    let in_interval = |info: &ExpenseInfo| info.date >= start && info.date <= end;
+
+    let singles = expenses
+        .single_expenses
+        .iter()
+        .filter(|expense| in_interval(&expense.info))
+        .map(|expense| ReportItem {
+            info: &expense.info,
+            amount: expense.amount,
+        });
+    let groups = expenses
+        .group_expenses
+        .iter()
+        .filter(|expense| in_interval(&expense.info))
+        .map(|expense| ReportItem {
+            info: &expense.info,
+            amount: expense.true_user_amount(),
+        });
+    let recurring = expenses
+        .recurring_expenses
+        .iter()
+        .map(|expense| ReportItem {
+            info: &expense.info,
+            amount: expense.amount_in_interval(start, end),
+        })
+        .filter(|item| !item.amount.is_zero());
+
+    singles.chain(groups).chain(recurring).collect()
+}
+
+/// a line of a report, the total of a category or tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportRow {
+    /// how deep the category is nested, 0 for top level categories and tags
+    pub depth: usize,
+    pub name: String,
+    pub total: Decimal,
+}
+
+/// what the user spent in an interval, grouped by category or tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// the number of expenses in the report
+    pub expense_count: usize,
+    /// every group followed by its own subgroups, each level ordered by the totals
+    pub rows: Vec<ReportRow>,
+    pub total: Decimal,
+}
+
+/// appends the children of `parent` ordered by their totals, each followed by its own children
+fn collect_level(
+    totals: &BTreeMap<Vec<String>, Decimal>,
+    parent: &[String],
+    rows: &mut Vec<ReportRow>,
+) {
+    let mut children: Vec<_> = totals
+        .iter()
+        .filter(|(path, _)| path.len() == parent.len() + 1 && path.starts_with(parent))
+        .collect();
+    children.sort_by(|(_, a), (_, b)| b.cmp(a));
+    for (path, total) in children {
+        rows.push(ReportRow {
+            depth: parent.len(),
+            name: path[parent.len()].clone(),
+            total: *total,
+        });
+        collect_level(totals, path, rows);
+    }
+}
+
+pub fn report(expenses: &Expenses, options: &ReportOptions) -> Report {
+    let today = Local::now().date_naive();
+    let end = options.to.unwrap_or(today);
+    let start = options.from.unwrap_or(end - RelativeDuration::months(1));
+
+    let items: Vec<_> = items_in_interval(expenses, start, end)
+        .into_iter()
+        .filter(|item| {
+            options
+                .tag
+                .as_ref()
+                .is_none_or(|tag| item.info.has_tag(tag))
+        })
+        .collect();
+
+    // every group is keyed by its path so that subcategories roll up into their parents
+    let mut totals: BTreeMap<Vec<String>, Decimal> = BTreeMap::new();
+    for item in &items {
+        let groups: BTreeSet<Vec<String>> = match options.by {
+            Grouping::Category => {
+                let path: Vec<String> = item.info.category.path().map(str::to_string).collect();
+                let depth = options.depth.unwrap_or(path.len()).min(path.len());
+                (1..=depth).map(|level| path[..level].to_vec()).collect()
+            }
+            Grouping::Tag if item.info.tags.is_empty() => {
+                BTreeSet::from([vec!["(untagged)".to_string()]])
+            }
+            Grouping::Tag => item
+                .info
+                .tags
+                .iter()
+                .map(|tag| vec![format!("#{tag}")])
+                .collect(),
+        };
+        for group in groups {
+            *totals.entry(group).or_insert(Decimal::ZERO) += item.amount;
+        }
+    }
+
+    let mut rows = Vec::new();
+    collect_level(&totals, &[], &mut rows);
+    Report {
+        start,
+        end,
+        expense_count: items.len(),
+        rows,
+        total: items.iter().map(|item| item.amount).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{add_single, amount, date};
+
+    use super::*;
+
+    fn report_with_depth(depth: Option<usize>) -> Report {
+        let mut expenses = Expenses::default();
+        add_single(
+            &mut expenses,
+            "2026-03-01",
+            "Food > Groceries",
+            "Market",
+            "20",
+        );
+        add_single(
+            &mut expenses,
+            "2026-03-02",
+            "Food > Groceries > Fruit",
+            "",
+            "5",
+        );
+        add_single(&mut expenses, "2026-03-03", "Food", "Lunch", "12");
+        add_single(&mut expenses, "2026-03-04", "Hobby", "Climbing", "45");
+        add_single(&mut expenses, "2026-04-01", "Food", "Too late", "100");
+        let options = ReportOptions {
+            from: Some(date(2026, 3, 1)),
+            to: Some(date(2026, 3, 31)),
+            depth,
+            ..Default::default()
+        };
+        report(&expenses, &options)
+    }
+
+    fn rows(report: &Report) -> Vec<(usize, &str, Decimal)> {
+        report
+            .rows
+            .iter()
+            .map(|row| (row.depth, row.name.as_str(), row.total))
+            .collect()
+    }
+
+    #[test]
+    fn subcategories_roll_up_into_their_parents() {
+        let report = report_with_depth(None);
+        assert_eq!(
+            rows(&report),
+            [
+                (0, "Hobby", amount("45")),
+                (0, "Food", amount("37")),
+                (1, "Groceries", amount("25")),
+                (2, "Fruit", amount("5")),
+            ]
+        );
+        assert_eq!(report.expense_count, 4);
+        assert_eq!(report.total, amount("82"));
+    }
+
+    #[test]
+    fn depth_limits_the_levels_but_not_the_totals() {
+        let report = report_with_depth(Some(1));
+        assert_eq!(
+            rows(&report),
+            [(0, "Hobby", amount("45")), (0, "Food", amount("37"))]
+        );
+        assert_eq!(report.total, amount("82"));
+
+        let report = report_with_depth(Some(2));
+        assert_eq!(
+            rows(&report),
+            [
+                (0, "Hobby", amount("45")),
+                (0, "Food", amount("37")),
+                (1, "Groceries", amount("25")),
+            ]
+        );
+    }
+}
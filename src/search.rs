@@ -0,0 +1,364 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use chrono::NaiveDate;
+use clap::Args;
+use rust_decimal::Decimal;
+
+use crate::{
+    expenses::{category::ExpenseCategory, RecordId},
+    report::Grouping,
+    ExpenseType, Expenses,
+};
+
+#[derive(Args, Debug, Default)]
+pub struct SearchFilter {
+    /// only show entries whose description contains this text (case-insensitive)
+    #[arg(short, long)]
+    pub text: Option<String>,
+    /// only show entries of this category or its subcategories
+    #[arg(short, long)]
+    pub category: Option<String>,
+    /// only show entries with this tag
+    #[arg(long)]
+    pub tag: Option<String>,
+    /// only show entries on or after this date (YYYY-MM-DD)
+    #[arg(long)]
+    pub from: Option<NaiveDate>,
+    /// only show entries on or before this date (YYYY-MM-DD)
+    #[arg(long)]
+    pub to: Option<NaiveDate>,
+    /// only show entries with at least this amount
+    #[arg(long)]
+    pub min: Option<Decimal>,
+    /// only show entries with at most this amount
+    #[arg(long)]
+    pub max: Option<Decimal>,
+    /// only show entries involving this person
+    #[arg(short, long)]
+    pub person: Option<String>,
+    /// only show entries of this type
+    #[arg(long = "type", value_enum)]
+    pub expense_type: Option<ExpenseType>,
+}
+
+/// a flattened view of any expense, debt or advancement used for searching and listing
+#[derive(Debug)]
+pub struct SearchEntry {
+    pub id: RecordId,
+    pub date: NaiveDate,
+    pub expense_type: ExpenseType,
+    pub category: Option<ExpenseCategory>,
+    pub description: Option<String>,
+    pub tags: BTreeSet<String>,
+    pub people: Vec<String>,
+    pub amount: Decimal,
+}
+
+impl SearchEntry {
+    fn matches(&self, filter: &SearchFilter) -> bool {
+        if let Some(text) = &filter.text {
+            let text = text.to_lowercase();
+            let description_matches = self
+                .description
+                .as_ref()
+                .is_some_and(|descr| descr.to_lowercase().contains(&text));
+            if !description_matches {
+                return false;
+            }
+        }
+        if let Some(category) = &filter.category {
+            let category_matches = self
+                .category
+                .as_ref()
+                .is_some_and(|cat| cat.is_within(category));
+            if !category_matches {
+                return false;
+            }
+        }
+        if let Some(tag) = &filter.tag {
+            if !self.tags.contains(tag.trim_start_matches('#')) {
+                return false;
+            }
+        }
+        if filter.from.is_some_and(|from| self.date < from) {
+            return false;
+        }
+        if filter.to.is_some_and(|to| self.date > to) {
+            return false;
+        }
+        if filter.min.is_some_and(|min| self.amount < min) {
+            return false;
+        }
+        if filter.max.is_some_and(|max| self.amount > max) {
+            return false;
+        }
+        if let Some(person) = &filter.person {
+            if !self.people.contains(person) {
+                return false;
+            }
+        }
+        if let Some(expense_type) = filter.expense_type {
+            if self.expense_type != expense_type {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Display for SearchEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:>5} {} {:10} {:22} {:30} {:20} {:8.2}",
+            self.id.to_string(),
+            self.date,
+            self.expense_type.to_string(),
+            self.category.as_ref().map_or("-", ExpenseCategory::name),
+            self.description.as_deref().unwrap_or("No description"),
+            self.people.join(", "),
+            self.amount
+        )
+    }
+}
+
+pub fn all_entries(expenses: &Expenses) -> Vec<SearchEntry> {
+    let singles = expenses.single_expenses.iter().map(|single| SearchEntry {
+        id: single.info.id,
+        date: single.info.date,
+        expense_type: ExpenseType::Single,
+        category: Some(single.info.category.clone()),
+        description: single.info.description.clone(),
+        tags: single.info.tags.clone(),
+        people: Vec::new(),
+        amount: single.amount,
+    });
+    let groups = expenses.group_expenses.iter().map(|group| SearchEntry {
+        id: group.info.id,
+        date: group.info.date,
+        expense_type: ExpenseType::Group,
+        category: Some(group.info.category.clone()),
+        description: group.info.description.clone(),
+        tags: group.info.tags.clone(),
+        people: group.people.clone(),
+        amount: group.total_amount(),
+    });
+    let recurring = expenses.recurring_expenses.iter().flat_map(|recurring| {
+        recurring
+            .occurrences
+            .iter()
+            .filter(|occurrence| occurrence.counts() && occurrence.group_id.is_none())
+            .map(move |occurrence| SearchEntry {
+                id: recurring.info.id,
+                date: occurrence.date,
+                expense_type: ExpenseType::Recurring,
+                category: Some(recurring.info.category.clone()),
+                description: recurring.info.description.clone(),
+                tags: recurring.info.tags.clone(),
+                people: Vec::new(),
+                amount: occurrence.amount,
+            })
+    });
+    let debts = expenses.debts_owed.iter().map(|debt| SearchEntry {
+        id: debt.expense.info.id,
+        date: debt.expense.info.date,
+        expense_type: ExpenseType::Todo,
+        category: Some(debt.expense.info.category.clone()),
+        description: debt.expense.info.description.clone(),
+        tags: debt.expense.info.tags.clone(),
+        people: vec![debt.person.clone()],
+        amount: debt.expense.amount,
+    });
+    let advancements = expenses
+        .unpaid_advancements
+        .iter()
+        .map(|advancement| SearchEntry {
+            id: advancement.id,
+            date: advancement.date,
+            expense_type: ExpenseType::Advance,
+            category: None,
+            description: advancement.description.clone(),
+            tags: BTreeSet::new(),
+            people: vec![advancement.person.clone()],
+            amount: advancement.amount,
+        });
+
+    let mut entries: Vec<_> = singles
+        .chain(groups)
+        .chain(recurring)
+        .chain(debts)
+        .chain(advancements)
+        .collect();
+    entries.sort_by_key(|entry| entry.date);
+    entries
+}
+
+pub fn search(expenses: &Expenses, filter: &SearchFilter) -> Vec<SearchEntry> {
+    all_entries(expenses)
+        .into_iter()
+        .filter(|entry| entry.matches(filter))
+        .collect()
+}
+
+/// the number of entries and their total amount per type, category or tag
+pub fn totals(entries: &[SearchEntry], by: Option<Grouping>) -> BTreeMap<String, (usize, Decimal)> {
+    let mut totals: BTreeMap<String, (usize, Decimal)> = BTreeMap::new();
+    for entry in entries {
+        let groups: Vec<String> = match by {
+            None => vec![entry.expense_type.to_string()],
+            Some(Grouping::Category) => vec![entry
+                .category
+                .as_ref()
+                .map_or("-".to_string(), ExpenseCategory::to_string)],
+            Some(Grouping::Tag) if entry.tags.is_empty() => vec!["(untagged)".into()],
+            Some(Grouping::Tag) => entry.tags.iter().map(|tag| format!("#{tag}")).collect(),
+        };
+        for group in groups {
+            let (count, total) = totals.entry(group).or_insert((0, Decimal::ZERO));
+            *count += 1;
+            *total += entry.amount;
+        }
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{add_advancement, add_debt, add_group, add_single, amount, date};
+
+    use super::*;
+
+    fn expenses() -> Expenses {
+        let mut expenses = Expenses::default();
+        add_single(
+            &mut expenses,
+            "2026-03-01",
+            "Food > Groceries",
+            "Market #weekly",
+            "23.40",
+        );
+        add_single(
+            &mut expenses,
+            "2026-03-05",
+            "Food",
+            "Lunch with Anna #work",
+            "12",
+        );
+        add_single(&mut expenses, "2026-03-20", "Hobby", "Climbing", "45");
+        add_group(
+            &mut expenses,
+            "2026-03-10",
+            "Pizza",
+            "10",
+            &[("Anna", "15")],
+            None,
+        );
+        add_debt(&mut expenses, "2026-03-12", "Bob", "30");
+        add_advancement(&mut expenses, "2026-03-15", "Anna", "50");
+        expenses
+    }
+
+    fn ids(filter: &SearchFilter) -> Vec<String> {
+        search(&expenses(), filter)
+            .iter()
+            .map(|entry| entry.id.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn no_filter_lists_everything_by_date() {
+        assert_eq!(
+            ids(&SearchFilter::default()),
+            ["1", "2", "4", "5", "6", "3"]
+        );
+    }
+
+    #[test]
+    fn text_ignores_case() {
+        let filter = SearchFilter {
+            text: Some("ANNA".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ids(&filter), ["2"]);
+    }
+
+    #[test]
+    fn amount_range_is_inclusive() {
+        let filter = SearchFilter {
+            min: Some(amount("12")),
+            max: Some(amount("25")),
+            ..Default::default()
+        };
+        assert_eq!(ids(&filter), ["1", "2", "4"]);
+    }
+
+    #[test]
+    fn date_range_is_inclusive() {
+        let filter = SearchFilter {
+            from: Some(date(2026, 3, 5)),
+            to: Some(date(2026, 3, 12)),
+            ..Default::default()
+        };
+        assert_eq!(ids(&filter), ["2", "4", "5"]);
+    }
+
+    #[test]
+    fn person_matches_groups_debts_and_advancements() {
+        let filter = |person: &str| SearchFilter {
+            person: Some(person.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ids(&filter("Anna")), ["4", "6"]);
+        assert_eq!(ids(&filter("Bob")), ["5"]);
+    }
+
+    #[test]
+    fn category_includes_subcategories() {
+        let filter = |category: &str| SearchFilter {
+            category: Some(category.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ids(&filter("Food")), ["1", "2", "4", "5"]);
+        assert_eq!(ids(&filter("Food>Groceries")), ["1"]);
+        assert!(ids(&filter("Foo")).is_empty());
+    }
+
+    #[test]
+    fn tag_with_or_without_hash() {
+        let filter = |tag: &str| SearchFilter {
+            tag: Some(tag.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ids(&filter("work")), ["2"]);
+        assert_eq!(ids(&filter("#weekly")), ["1"]);
+    }
+
+    #[test]
+    fn filters_combine() {
+        let filter = SearchFilter {
+            category: Some("Food".to_string()),
+            from: Some(date(2026, 3, 2)),
+            max: Some(amount("25")),
+            expense_type: Some(ExpenseType::Group),
+            ..Default::default()
+        };
+        assert_eq!(ids(&filter), ["4"]);
+        let filter = SearchFilter {
+            expense_type: Some(ExpenseType::Single),
+            person: Some("Anna".to_string()),
+            ..Default::default()
+        };
+        assert!(ids(&filter).is_empty());
+    }
+
+    #[test]
+    fn totals_by_type() {
+        let entries = search(&expenses(), &SearchFilter::default());
+        let totals = totals(&entries, None);
+        assert_eq!(totals["single"], (3, amount("80.40")));
+        assert_eq!(totals["group"], (1, amount("25")));
+    }
+}
@@ -0,0 +1,167 @@
+use clap::ValueEnum;
+use rust_decimal::Decimal;
+
+use crate::{
+    ledger::{Ledger, LedgerItem},
+    KakeboConfig,
+};
+
+/// how a statement is written
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StatementFormat {
+    #[default]
+    Text,
+    Markdown,
+}
+
+fn date(item: &LedgerItem) -> String {
+    item.date.map_or_else(String::new, |date| date.to_string())
+}
+
+/// what the user owes the person or the person owes the user, from the point of view of the user
+fn direction(amount: Decimal) -> &'static str {
+    if amount < Decimal::ZERO {
+        "I owe you"
+    } else {
+        "you owe me"
+    }
+}
+
+impl Ledger {
+    /// a message to the person listing everything that is still open between the two
+    pub fn statement(&self, format: StatementFormat, config: &KakeboConfig) -> String {
+        let currency = config.currency;
+        let open_items: Vec<_> = self
+            .items
+            .iter()
+            .filter(|item| !item.is_settled())
+            .collect();
+        let mut message = format!("Hi {}, this is what is open between us:\n\n", self.person);
+        match format {
+            StatementFormat::Text => {
+                for item in &open_items {
+                    let amount = item.they_owe - item.you_owe;
+                    message.push_str(&format!(
+                        "{:10} {:30} {:8.2}{} {}\n",
+                        date(item),
+                        item.title,
+                        amount.abs(),
+                        currency,
+                        direction(amount)
+                    ));
+                }
+            }
+            StatementFormat::Markdown => {
+                message.push_str("| Date | Description | You owe me | I owe you |\n");
+                message.push_str("|------|-------------|-----------:|----------:|\n");
+                for item in &open_items {
+                    message.push_str(&format!(
+                        "| {} | {} | {:.2}{currency} | {:.2}{currency} |\n",
+                        date(item),
+                        item.title.replace('|', "\\|"),
+                        item.they_owe,
+                        item.you_owe,
+                    ));
+                }
+            }
+        }
+        message.push('\n');
+
+        let balance = self.balance();
+        let total = if balance.is_zero() {
+            "we are even".to_string()
+        } else {
+            format!("{} {:.2}{}", direction(balance), balance.abs(), currency)
+        };
+        match format {
+            StatementFormat::Text => message.push_str(&format!("In total {}.\n", total)),
+            StatementFormat::Markdown => message.push_str(&format!("**In total {}.**\n", total)),
+        }
+        if balance > Decimal::ZERO {
+            if let Some(instructions) = &config.payment_instructions {
+                message.push_str(&format!("\n{}\n", instructions));
+            }
+        }
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{add_advancement, add_debt, add_group};
+    use crate::Expenses;
+
+    use super::*;
+
+    /// Anna owes 12 for a dinner and 40 for an advancement, the user owes her 15
+    fn expenses() -> Expenses {
+        let mut expenses = Expenses::default();
+        add_group(
+            &mut expenses,
+            "2026-03-01",
+            "Dinner",
+            "10",
+            &[("Anna", "12")],
+            None,
+        );
+        add_advancement(&mut expenses, "2026-03-02", "Anna", "40");
+        add_debt(&mut expenses, "2026-03-03", "Anna", "15");
+        expenses.config.payment_instructions = Some("IBAN DE00 1234".to_string());
+        expenses
+    }
+
+    #[test]
+    fn text_statement_lists_the_open_items() {
+        let expenses = expenses();
+        let statement = expenses
+            .ledger("Anna")
+            .statement(StatementFormat::Text, &expenses.config);
+        assert_eq!(
+            statement,
+            "Hi Anna, this is what is open between us:\n\
+             \n\
+             2026-03-01 Dinner                            12.00€ you owe me\n\
+             2026-03-03 Food                              15.00€ I owe you\n\
+             2026-03-02 Advancement                       40.00€ you owe me\n\
+             \n\
+             In total you owe me 37.00€.\n\
+             \n\
+             IBAN DE00 1234\n"
+        );
+    }
+
+    #[test]
+    fn markdown_statement_has_a_table() {
+        let mut expenses = expenses();
+        expenses.unpaid_advancements.clear();
+        let statement = expenses
+            .ledger("Anna")
+            .statement(StatementFormat::Markdown, &expenses.config);
+        assert_eq!(
+            statement,
+            "Hi Anna, this is what is open between us:\n\
+             \n\
+             | Date | Description | You owe me | I owe you |\n\
+             |------|-------------|-----------:|----------:|\n\
+             | 2026-03-01 | Dinner | 12.00€ | 0.00€ |\n\
+             | 2026-03-03 | Food | 0.00€ | 15.00€ |\n\
+             \n\
+             **In total I owe you 3.00€.**\n"
+        );
+    }
+
+    #[test]
+    fn settled_items_are_left_out() {
+        let mut expenses = expenses();
+        expenses.unpaid_advancements.clear();
+        expenses.group_expenses[0].paid_amounts[0] = Some(Decimal::from(12));
+        expenses.debts_owed.clear();
+        let statement = expenses
+            .ledger("Anna")
+            .statement(StatementFormat::Text, &expenses.config);
+        assert_eq!(
+            statement,
+            "Hi Anna, this is what is open between us:\n\n\nIn total we are even.\n"
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use std::{fs::File, path::Path};
+
+use age::{secrecy::Secret, Decryptor, Encryptor};
+use lz4_flex::frame::{FrameDecoder, FrameEncoder};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::KakeboError;
+
+/// reads an encrypted database, a file that does not exist yet is read as an empty database
+pub fn parse_file<T>(path: &Path, passphrase: &str) -> Result<T, KakeboError>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let mut file = File::open(path)?;
+    let decryptor = match Decryptor::new(&mut file)? {
+        Decryptor::Passphrase(decr) => decr,
+        _ => unreachable!(),
+    };
+    let mut decrypt_reader = decryptor.decrypt(&Secret::new(passphrase.to_owned()), None)?;
+    let mut decode_reader = FrameDecoder::new(&mut decrypt_reader);
+    let expenses = rmp_serde::decode::from_read(&mut decode_reader)?;
+    Ok(expenses)
+}
+
+pub fn write_file<T>(path: &Path, expenses: &T, passphrase: &str) -> Result<(), KakeboError>
+where
+    T: Serialize,
+{
+    let mut file = File::create(path)?;
+    let encryptor = Encryptor::with_user_passphrase(Secret::new(passphrase.to_owned()));
+    let mut encrypt_writer = encryptor.wrap_output(&mut file)?;
+    let mut compress_writer = FrameEncoder::new(&mut encrypt_writer);
+    rmp_serde::encode::write(&mut compress_writer, expenses)?;
+    compress_writer.finish()?;
+    encrypt_writer.finish()?;
+    Ok(())
+}
+
+/// a function that simplifies tranforming older versions of the data structure into new ones
+pub fn transform<Src, Dst>(path: &Path, passphrase: &str) -> Result<(), KakeboError>
+where
+    Src: for<'de> Deserialize<'de> + Default,
+    Dst: Serialize + From<Src>,
+{
+    let from_content: Src = parse_file(path, passphrase)?;
+    let to_content: Dst = from_content.into();
+    write_file(path, &to_content, passphrase)
+}
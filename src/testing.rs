@@ -0,0 +1,110 @@
+//! helpers that build records through the same prompts as the command line
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use crate::{
+    expenses::{
+        advancement::Advancement, debt::Debt, group_expense::GroupExpense,
+        recurring_expense::RecurringExpense, single_expense::SingleExpense, ExpenseInfo, RecordId,
+    },
+    prompter::ScriptedPrompter,
+    DisplayableExpense, Environment, Expenses, KakeboConfig,
+};
+
+pub fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+pub fn amount(amount: &str) -> Decimal {
+    amount.parse().unwrap()
+}
+
+/// an environment in which every person and category has to be typed in
+pub fn environment(category: &str) -> Environment {
+    Environment {
+        people: Default::default(),
+        tags: Default::default(),
+        categories: vec![category.to_string()],
+    }
+}
+
+/// `description` may end with tags, e.g. `Lunch #work`
+pub fn info(date: &str, category: &str, description: &str) -> ExpenseInfo {
+    let (description, tags) = description.split_once(" #").unwrap_or((description, ""));
+    let mut prompter = ScriptedPrompter::new([date, category, description, tags]);
+    ExpenseInfo::new(&mut prompter, &environment(category)).unwrap()
+}
+
+pub fn add_single(
+    expenses: &mut Expenses,
+    date: &str,
+    category: &str,
+    description: &str,
+    amount: &str,
+) -> RecordId {
+    let mut single = SingleExpense {
+        amount: self::amount(amount),
+        info: info(date, category, description),
+    };
+    single.set_id(expenses.new_id());
+    expenses.single_expenses.push(single);
+    expenses.last_id
+}
+
+/// a group expense split into exact amounts, paid by the user unless there is a `payer`
+pub fn add_group(
+    expenses: &mut Expenses,
+    date: &str,
+    description: &str,
+    user_share: &str,
+    shares: &[(&str, &str)],
+    payer: Option<&str>,
+) -> RecordId {
+    let config = KakeboConfig::default();
+    let mut answers = vec!["exact amounts", user_share];
+    for (person, share) in shares {
+        answers.extend([*person, *share]);
+    }
+    answers.extend(["<esc>", payer.unwrap_or(&config.user_name)]);
+    let mut prompter = ScriptedPrompter::new(answers);
+    let info = info(date, "Food", description);
+    let mut group =
+        GroupExpense::split(&mut prompter, info, &environment("Food"), &config).unwrap();
+    group.set_id(expenses.new_id());
+    expenses.group_expenses.push(group);
+    expenses.last_id
+}
+
+/// a debt of the user to `person`
+pub fn add_debt(expenses: &mut Expenses, date: &str, person: &str, amount: &str) -> RecordId {
+    let config = KakeboConfig::default();
+    let mut prompter = ScriptedPrompter::new([person, date, "Food", "", "", amount, "y"]);
+    let mut debt = Debt::new(&mut prompter, &environment("Food"), &config).unwrap();
+    debt.set_id(expenses.new_id());
+    expenses.debts_owed.push(debt);
+    expenses.last_id
+}
+
+/// an advancement the user gave to `person`
+pub fn add_advancement(
+    expenses: &mut Expenses,
+    date: &str,
+    person: &str,
+    amount: &str,
+) -> RecordId {
+    let config = KakeboConfig::default();
+    let mut prompter = ScriptedPrompter::new([person, date, "", amount]);
+    let mut advancement = Advancement::new(&mut prompter, &environment("Food"), &config).unwrap();
+    advancement.set_id(expenses.new_id());
+    expenses.unpaid_advancements.push(advancement);
+    expenses.last_id
+}
+
+/// a recurring expense of 50 for the user alone, e.g. `recurring("2026-01-31", "Month(s)", "1")`
+pub fn recurring(first: &str, unit: &str, every: &str) -> RecurringExpense {
+    let config = KakeboConfig::default();
+    let mut prompter =
+        ScriptedPrompter::new([first, "Rent", "", "", "n", "50", unit, every, "n", "y"]);
+    RecurringExpense::new(&mut prompter, &environment("Rent"), &config).unwrap()
+}
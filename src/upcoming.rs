@@ -0,0 +1,187 @@
+use chrono::{Datelike, Days, Months, NaiveDate};
+use rust_decimal::Decimal;
+
+use crate::{
+    expenses::recurring_expense::{OccurrenceStatus, RecurringExpense},
+    Expenses,
+};
+
+/// an occurrence of a recurring expense that still has to be paid
+#[derive(Debug)]
+pub struct UpcomingBill<'a> {
+    pub expense: &'a RecurringExpense,
+    pub date: NaiveDate,
+    /// the user's part of the bill
+    pub amount: Decimal,
+}
+
+/// the recurring expenses due in the coming days and what they will cost next month
+#[derive(Debug)]
+pub struct Forecast<'a> {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// the bills due from `start` until `end`, ordered by date
+    pub bills: Vec<UpcomingBill<'a>>,
+    /// the first day of next month and the projected recurring expenses of that month
+    pub next_month: Option<(NaiveDate, Decimal)>,
+    /// the recurring expenses that end from `start` until `end`
+    pub ending: Vec<&'a RecurringExpense>,
+}
+
+impl Forecast<'_> {
+    pub fn total(&self) -> Decimal {
+        self.bills.iter().map(|bill| bill.amount).sum()
+    }
+}
+
+fn upcoming_bills(expenses: &Expenses, start: NaiveDate, end: NaiveDate) -> Vec<UpcomingBill<'_>> {
+    let mut bills: Vec<_> = expenses
+        .recurring_expenses
+        .iter()
+        .flat_map(|expense| {
+            expense
+                .occurrences_in_interval(start, end)
+                .into_iter()
+                .filter(|occurrence| occurrence.status == OccurrenceStatus::Due)
+                .map(move |occurrence| UpcomingBill {
+                    expense,
+                    date: occurrence.date,
+                    amount: expense.user_amount(&occurrence),
+                })
+        })
+        .collect();
+    bills.sort_by_key(|bill| bill.date);
+    bills
+}
+
+/// the first and last day of the month after the one containing `date`
+fn next_month(date: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let start = date.with_day(1)?.checked_add_months(Months::new(1))?;
+    let end = start.checked_add_months(Months::new(1))?.pred_opt()?;
+    Some((start, end))
+}
+
+/// the bills from `today` until `days` later
+pub fn forecast(expenses: &Expenses, today: NaiveDate, days: u32) -> Forecast<'_> {
+    let start = today;
+    let end = start
+        .checked_add_days(Days::new(days.into()))
+        .unwrap_or(NaiveDate::MAX);
+
+    let next_month = next_month(start).map(|(first, last)| {
+        let projected = expenses
+            .recurring_expenses
+            .iter()
+            .map(|expense| expense.amount_in_interval(first, last))
+            .sum();
+        (first, projected)
+    });
+    let ending = expenses
+        .recurring_expenses
+        .iter()
+        .filter(|expense| {
+            expense
+                .end_date()
+                .is_some_and(|end_date| end_date >= start && end_date <= end)
+        })
+        .collect();
+
+    Forecast {
+        start,
+        end,
+        bills: upcoming_bills(expenses, start, end),
+        next_month,
+        ending,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expenses::recurring_expense::OccurrenceStatus;
+    use crate::prompter::ScriptedPrompter;
+    use crate::testing::{amount, date, environment, recurring};
+    use crate::KakeboConfig;
+
+    use super::*;
+
+    /// rent every month from January on and a course every week until 2026-03-20
+    fn expenses() -> Expenses {
+        let mut expenses = Expenses::default();
+        expenses
+            .recurring_expenses
+            .push(recurring("2026-01-01", "Month(s)", "1"));
+        let mut prompter = ScriptedPrompter::new([
+            "2026-01-01",
+            "Course",
+            "",
+            "",
+            "n",
+            "20",
+            "Week(s)",
+            "1",
+            "y",
+            "2026-03-20",
+            "y",
+        ]);
+        let course = RecurringExpense::new(
+            &mut prompter,
+            &environment("Course"),
+            &KakeboConfig::default(),
+        )
+        .unwrap();
+        expenses.recurring_expenses.push(course);
+        expenses
+    }
+
+    fn bills(forecast: &Forecast) -> Vec<(NaiveDate, Decimal)> {
+        forecast
+            .bills
+            .iter()
+            .map(|bill| (bill.date, bill.amount))
+            .collect()
+    }
+
+    #[test]
+    fn the_window_starts_on_the_given_day() {
+        let expenses = expenses();
+        let month = forecast(&expenses, date(2026, 3, 5), 30);
+        assert_eq!(
+            (month.start, month.end),
+            (date(2026, 3, 5), date(2026, 4, 4))
+        );
+        assert_eq!(
+            bills(&month),
+            [
+                (date(2026, 3, 5), amount("20")),
+                (date(2026, 3, 12), amount("20")),
+                (date(2026, 3, 19), amount("20")),
+                (date(2026, 4, 1), amount("50")),
+            ]
+        );
+        assert_eq!(month.total(), amount("110"));
+        assert_eq!(month.next_month, Some((date(2026, 4, 1), amount("50"))));
+        let ending: Vec<_> = month
+            .ending
+            .iter()
+            .map(|expense| expense.info.category.name())
+            .collect();
+        assert_eq!(ending, ["Course"]);
+
+        let today = forecast(&expenses, date(2026, 3, 5), 0);
+        assert_eq!(bills(&today), [(date(2026, 3, 5), amount("20"))]);
+        assert!(today.ending.is_empty());
+    }
+
+    #[test]
+    fn paid_occurrences_are_not_upcoming() {
+        let mut expenses = expenses();
+        expenses.materialize_occurrences(date(2026, 3, 1));
+        expenses.materialize_occurrences(date(2026, 3, 5));
+        let course = &mut expenses.recurring_expenses[1];
+        assert_eq!(course.occurrences[0].date, date(2026, 3, 5));
+        course.occurrences[0].status = OccurrenceStatus::Paid;
+
+        let forecast = forecast(&expenses, date(2026, 3, 5), 10);
+        assert_eq!(bills(&forecast), [(date(2026, 3, 12), amount("20"))]);
+    }
+}